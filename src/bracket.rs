@@ -0,0 +1,125 @@
+use std::ops::Range;
+
+use crate::data::*;
+use crate::dimensions::*;
+use crate::highlight::*;
+
+const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+// Return the opening and closing bracket of the pair 'c' belongs to
+pub fn bracket_pair(c: char) -> Option<(char, char)> {
+    PAIRS
+        .iter()
+        .find(|(open, close)| c == *open || c == *close)
+        .copied()
+}
+
+/*
+ * The brackets of a row along with their index in 'chars'. Brackets in
+ * strings and comments, as classified by the highlighting, are left out.
+ */
+fn row_brackets(row: &EditRow) -> Vec<(usize, char)> {
+    let mut brackets = Vec::new();
+    let mut rx = 0;
+    for (cx, c) in row.chars.chars().enumerate() {
+        let in_text = matches!(
+            row.highlight.get(rx),
+            Some(Highlight::Comment) | Some(Highlight::Str)
+        );
+        if bracket_pair(c).is_some() && !in_text {
+            brackets.push((cx, c));
+        }
+        rx += if c == '\t' {
+            row.tabstop as usize - rx % row.tabstop as usize
+        } else {
+            1
+        };
+    }
+    brackets
+}
+
+/*
+ * Find the bracket matching the one at 'pos', or if there is none, the one
+ * just left of it, within the rows 'within'. Only brackets of the same kind
+ * are counted, so that a missing ')' doesn't stop a '{' from finding its '}'.
+ */
+pub fn find_match(
+    rows: &[EditRow],
+    pos: Position,
+    within: Range<usize>,
+) -> Option<(Position, Position)> {
+    let y = pos.y as usize;
+    let row = rows.get(y)?;
+    let brackets = row_brackets(row);
+    let x = pos.x as usize;
+    let &(x, c) = brackets
+        .iter()
+        .find(|(cx, _)| *cx == x)
+        .or_else(|| brackets.iter().find(|(cx, _)| x > 0 && *cx == x - 1))?;
+    let (open, close) = bracket_pair(c)?;
+    let at = Position::new(x as u16, y as u16);
+
+    let mut depth = 0;
+    if c == open {
+        for (cy, row) in rows.iter().enumerate().take(within.end).skip(y) {
+            for (cx, b) in row_brackets(row) {
+                if cy == y && cx <= x {
+                    continue;
+                }
+                if b == open {
+                    depth += 1;
+                } else if b == close {
+                    if depth == 0 {
+                        return Some((at, Position::new(cx as u16, cy as u16)));
+                    }
+                    depth -= 1;
+                }
+            }
+        }
+    } else {
+        for (cy, row) in rows.iter().enumerate().take(y + 1).skip(within.start).rev() {
+            for (cx, b) in row_brackets(row).into_iter().rev() {
+                if cy == y && cx >= x {
+                    continue;
+                }
+                if b == close {
+                    depth += 1;
+                } else if b == open {
+                    if depth == 0 {
+                        return Some((at, Position::new(cx as u16, cy as u16)));
+                    }
+                    depth -= 1;
+                }
+            }
+        }
+    }
+    None
+}
+
+/*
+ * Find the brackets without a partner: closing brackets that don't match
+ * the innermost open one, and open brackets that are never closed.
+ */
+pub fn unbalanced(rows: &[EditRow]) -> Vec<Position> {
+    let mut stack: Vec<(Position, char)> = Vec::new();
+    let mut unbalanced = Vec::new();
+
+    for (cy, row) in rows.iter().enumerate() {
+        for (cx, c) in row_brackets(row) {
+            let pos = Position::new(cx as u16, cy as u16);
+            match bracket_pair(c) {
+                Some((open, _)) if c == open => stack.push((pos, c)),
+                Some((open, _)) => {
+                    if stack.last().is_some_and(|(_, top)| *top == open) {
+                        stack.pop();
+                    } else {
+                        unbalanced.push(pos);
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+    unbalanced.extend(stack.into_iter().map(|(pos, _)| pos));
+    unbalanced
+}
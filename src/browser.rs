@@ -0,0 +1,111 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+// Name of the entry that goes up to the parent directory
+pub const PARENT: &str = "..";
+
+// A file or directory listed by the directory browser
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl Entry {
+    // Name, size and modification time, with the names padded to 'width'
+    pub fn line(&self, width: usize) -> String {
+        let name = if self.is_dir && self.name != PARENT {
+            format!("{}/", self.name)
+        } else {
+            self.name.clone()
+        };
+        let size = if self.is_dir {
+            String::new()
+        } else {
+            format_size(self.size)
+        };
+        let modified = self.modified.map(format_time).unwrap_or_default();
+        format!("{:<width$}  {:>6}  {}", name, size, modified)
+    }
+}
+
+/*
+ * The entries of a directory, '..' first, then the directories and the
+ * files, each sorted by name.
+ */
+pub fn list_dir(dir: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries: Vec<Entry> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| {
+            let metadata = entry.metadata().ok();
+            Entry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                // Follow symbolic links to directories
+                is_dir: entry.path().is_dir(),
+                size: metadata.as_ref().map_or(0, |m| m.len()),
+                modified: metadata.and_then(|m| m.modified().ok()),
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then(a.name.cmp(&b.name)));
+    entries.insert(
+        0,
+        Entry {
+            name: PARENT.to_string(),
+            is_dir: true,
+            size: 0,
+            modified: None,
+        },
+    );
+    Ok(entries)
+}
+
+// A size like '512', '1.5K' or '23M'
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["", "K", "M", "G", "T"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}", size)
+    } else if size < 10.0 {
+        format!("{:.1}{}", size, UNITS[unit])
+    } else {
+        format!("{:.0}{}", size, UNITS[unit])
+    }
+}
+
+/*
+ * A time as 'YYYY-MM-DD HH:MM' in UTC. The date is computed from the days
+ * since the epoch with the civil_from_days algorithm of Howard Hinnant.
+ */
+fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60
+    )
+}
@@ -0,0 +1,341 @@
+use regex::Regex;
+use regex::RegexBuilder;
+
+use std::fs;
+
+use crate::commands::*;
+use crate::events::*;
+use crate::syntax::*;
+
+// Names of the ex commands, each with its shortest abbreviation
+const EX_COMMANDS: &[(&str, &str)] = &[
+    ("write", "w"),
+    ("wq", "wq"),
+    ("xit", "x"),
+    ("edit", "e"),
+    ("quit", "q"),
+    ("set", "se"),
+    ("syntax", "sy"),
+    ("substitute", "s"),
+    ("sort", "sor"),
+    ("grep", "gr"),
+    ("make", "mak"),
+    ("cnext", "cn"),
+    ("cprevious", "cp"),
+];
+
+// Options of the set command, for completion
+const OPTIONS: &[&str] = &[
+    "tabstop=",
+    "shiftwidth=",
+    "expandtab",
+    "noexpandtab",
+    "number",
+    "nonumber",
+    "autopairs",
+    "noautopairs",
+];
+
+// The first and last line of a range
+type Lines = (usize, usize);
+
+/*
+ * A command line parsed. Ranges of lines are counted from 0 and include
+ * the last line.
+ */
+#[derive(Debug)]
+pub enum ExCommand {
+    Write(Option<String>),
+    WriteQuit,
+    Edit(Option<String>, bool), // the file, or the current one again, and discard changes
+    Quit(bool),                 // discard changes
+    Set(Vec<String>),
+    Syntax(String),
+    Substitute {
+        first: usize,
+        last: usize,
+        pattern: Regex,
+        replacement: String,
+        global: bool,
+    },
+    Sort {
+        first: usize,
+        last: usize,
+        reverse: bool,
+    },
+    Filter {
+        first: usize,
+        last: usize,
+        command: String,
+    },
+    Grep(String),
+    Make(Option<String>), // the build command, or the configured one
+    Goto(usize),
+    Run(EditorEvent), // a command of the keymap by its name
+}
+
+/*
+ * Parse a command line like 'w other.c', 'e! file', 'set ts=4', '%s/a/b/g',
+ * '10,20sort' or '%!fmt'. Lines of a range are given as numbers, '.' for the line
+ * 'current', '$' for the last of 'total' lines and '%' for all of them.
+ * Return None for an empty line.
+ */
+pub fn parse_command(
+    line: &str,
+    current: usize,
+    total: usize,
+) -> Result<Option<ExCommand>, String> {
+    let line = line.trim();
+    let (range, rest) = parse_range(line, current, total)?;
+    let name_len = rest
+        .find(|ch: char| !ch.is_ascii_alphabetic() && ch != '-')
+        .unwrap_or(rest.len());
+    let (name, rest) = rest.split_at(name_len);
+    let (force, arg) = match rest.strip_prefix('!') {
+        Some(arg) => (true, arg.trim()),
+        None => (false, rest.trim()),
+    };
+    let file = (!arg.is_empty()).then(|| arg.to_string());
+
+    if name.is_empty() && force {
+        return match range {
+            Some(_) if arg.is_empty() => Err("Usage: N,M!command".to_string()),
+            Some((first, last)) => Ok(Some(ExCommand::Filter {
+                first,
+                last,
+                command: arg.to_string(),
+            })),
+            None => Err("Give the lines to filter, like %!sort".to_string()),
+        };
+    }
+    if name.is_empty() {
+        return match (range, arg.is_empty()) {
+            (None, true) => Ok(None),
+            (Some((_, last)), true) => Ok(Some(ExCommand::Goto(last))),
+            _ => Err(format!("Not an editor command: {}", line)),
+        };
+    }
+    let (first, last) = range.unwrap_or((current, current));
+    let command = match ex_command(name) {
+        Some("write") => ExCommand::Write(file),
+        Some("wq") | Some("xit") => ExCommand::WriteQuit,
+        Some("edit") => ExCommand::Edit(file, force),
+        Some("quit") => ExCommand::Quit(force),
+        Some("set") => ExCommand::Set(arg.split_whitespace().map(String::from).collect()),
+        Some("syntax") => ExCommand::Syntax(arg.to_string()),
+        Some("substitute") => parse_substitute(arg, first, last)?,
+        Some("sort") => {
+            let (first, last) = range.unwrap_or((0, total.saturating_sub(1)));
+            ExCommand::Sort {
+                first,
+                last,
+                reverse: force,
+            }
+        }
+        Some("grep") => ExCommand::Grep(arg.to_string()),
+        Some("make") => ExCommand::Make(file),
+        Some("cnext") => ExCommand::Run(EditorEvent::Control(ControlEvent::NextError)),
+        Some("cprevious") => ExCommand::Run(EditorEvent::Control(ControlEvent::PreviousError)),
+        _ => match find_command(name) {
+            Some(command) if arg.is_empty() => ExCommand::Run(command.event),
+            _ => return Err(format!("Not an editor command: {}", line)),
+        },
+    };
+    Ok(Some(command))
+}
+
+// The full name of an ex command given by a prefix at least as long as its abbreviation
+fn ex_command(name: &str) -> Option<&'static str> {
+    EX_COMMANDS
+        .iter()
+        .find(|(full, abbreviation)| full.starts_with(name) && name.starts_with(abbreviation))
+        .map(|(full, _)| *full)
+}
+
+fn parse_range(line: &str, current: usize, total: usize) -> Result<(Option<Lines>, &str), String> {
+    let last_line = total.saturating_sub(1);
+    if let Some(rest) = line.strip_prefix('%') {
+        return Ok((Some((0, last_line)), rest));
+    }
+    let address_len = line
+        .find(|ch: char| !ch.is_ascii_digit() && !matches!(ch, '.' | '$' | ','))
+        .unwrap_or(line.len());
+    let (range, rest) = line.split_at(address_len);
+    if range.is_empty() {
+        return Ok((None, rest));
+    }
+    let address = |s: &str| match s {
+        "." => Ok(current),
+        "$" => Ok(last_line),
+        n => match n.parse::<usize>() {
+            Ok(n) => Ok(n.saturating_sub(1).min(last_line)),
+            Err(_) => Err(format!("Invalid range: {}", range)),
+        },
+    };
+    let (first, last) = match range.split_once(',') {
+        Some((first, last)) => (address(first)?, address(last)?),
+        None => (address(range)?, address(range)?),
+    };
+    if first > last {
+        return Err(format!("Backwards range: {}", range));
+    }
+    Ok((Some((first, last)), rest))
+}
+
+/*
+ * Parse the '/pattern/replacement/flags' of a substitute command. Any
+ * punctuation can be used instead of '/'. In the replacement '&' and
+ * '\1' to '\9' stand for the match and its groups, flag 'g' replaces all
+ * the matches on a line and 'i' ignores case.
+ */
+fn parse_substitute(arg: &str, first: usize, last: usize) -> Result<ExCommand, String> {
+    let mut chars = arg.chars();
+    let delimiter = match chars.next() {
+        Some(ch) if !ch.is_alphanumeric() && !ch.is_whitespace() && ch != '\\' => ch,
+        _ => return Err("Usage: s/pattern/replacement/flags".to_string()),
+    };
+    let mut fields = vec![String::new()];
+    let mut escaped = false;
+    for ch in chars {
+        if escaped {
+            let field = fields.last_mut().unwrap();
+            if ch != delimiter {
+                field.push('\\');
+            }
+            field.push(ch);
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == delimiter && fields.len() < 3 {
+            fields.push(String::new());
+        } else {
+            fields.last_mut().unwrap().push(ch);
+        }
+    }
+    let (pattern, replacement, flags) = match &fields[..] {
+        [pattern] => (pattern, "", ""),
+        [pattern, replacement] => (pattern, replacement.as_str(), ""),
+        [pattern, replacement, flags] => (pattern, replacement.as_str(), flags.as_str()),
+        _ => unreachable!(),
+    };
+    if pattern.is_empty() {
+        return Err("Empty pattern".to_string());
+    }
+    if let Some(flag) = flags.chars().find(|ch| !matches!(ch, 'g' | 'i')) {
+        return Err(format!("Unknown flag '{}'", flag));
+    }
+    let pattern = RegexBuilder::new(pattern)
+        .case_insensitive(flags.contains('i'))
+        .build()
+        .map_err(|e| format!("Invalid pattern: {}", e))?;
+    Ok(ExCommand::Substitute {
+        first,
+        last,
+        pattern,
+        replacement: regex_replacement(replacement),
+        global: flags.contains('g'),
+    })
+}
+
+// Translate '&' and '\1' of a replacement to the '${0}' and '${1}' of the regex crate
+fn regex_replacement(replacement: &str) -> String {
+    let mut result = String::new();
+    let mut chars = replacement.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '&' => result.push_str("${0}"),
+            '$' => result.push_str("$$"),
+            '\\' => match chars.next() {
+                Some(digit) if digit.is_ascii_digit() => {
+                    result.push_str(&format!("${{{}}}", digit))
+                }
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(ch) => result.push(ch),
+                None => result.push('\\'),
+            },
+            ch => result.push(ch),
+        }
+    }
+    result
+}
+
+/*
+ * The command lines that complete the last word of 'line': the names of
+ * commands for the first word, file names after 'w' and 'e', syntax
+ * names after 'syntax' and options after 'set'.
+ */
+pub fn complete_command(line: &str) -> Vec<String> {
+    let (head, word) = match line.rfind(char::is_whitespace) {
+        Some(at) => line.split_at(at + 1),
+        None => {
+            // Keep a range in front of the command
+            let at = line
+                .find(|ch: char| ch.is_ascii_alphabetic())
+                .unwrap_or(line.len());
+            let (range, name) = line.split_at(at);
+            let mut names: Vec<&str> = EX_COMMANDS
+                .iter()
+                .map(|(full, _)| *full)
+                .chain(COMMANDS.iter().map(|command| command.name))
+                .filter(|full| full.starts_with(name))
+                .collect();
+            names.sort();
+            names.dedup();
+            return names
+                .into_iter()
+                .map(|name| format!("{}{}", range, name))
+                .collect();
+        }
+    };
+    let name = head.split_whitespace().next().unwrap_or("");
+    let name = name.trim_end_matches('!');
+    let candidates = match ex_command(name) {
+        Some("write") | Some("edit") => complete_path(word),
+        Some("syntax") => complete_syntax(word),
+        Some("set") => OPTIONS
+            .iter()
+            .filter(|option| option.starts_with(word))
+            .map(|option| option.to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+    candidates
+        .into_iter()
+        .map(|candidate| format!("{}{}", head, candidate))
+        .collect()
+}
+
+// Names of syntaxes that start with 'word'
+pub fn complete_syntax(word: &str) -> Vec<String> {
+    HLDB.iter()
+        .map(|syntax| syntax.filetype.to_string().to_lowercase())
+        .chain(std::iter::once("none".to_string()))
+        .filter(|name| name.starts_with(word))
+        .collect()
+}
+
+// Files in the directory of 'word' that start with its last component, directories with a '/'
+pub fn complete_path(word: &str) -> Vec<String> {
+    let (dir, prefix) = match word.rfind('/') {
+        Some(at) => word.split_at(at + 1),
+        None => ("", word),
+    };
+    let Ok(entries) = fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            // Hidden files only when asked for
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir, name, slash))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
@@ -0,0 +1,461 @@
+use crate::events::*;
+
+// A command that can be bound to keys, by the name used in keymap files
+pub struct Command {
+    pub name: &'static str,
+    pub event: EditorEvent,
+    pub help: &'static str,
+}
+
+const fn command(name: &'static str, event: EditorEvent, help: &'static str) -> Command {
+    Command { name, event, help }
+}
+
+const fn control(event: ControlEvent) -> EditorEvent {
+    EditorEvent::Control(event)
+}
+
+const fn cursor(key: CursorKey) -> EditorEvent {
+    EditorEvent::Cursor(key)
+}
+
+const fn select(key: CursorKey) -> EditorEvent {
+    EditorEvent::Select(key)
+}
+
+pub const COMMANDS: &[Command] = &[
+    command("quit", control(ControlEvent::Quit), "Quit the editor"),
+    command("save", control(ControlEvent::Save), "Save the file"),
+    command(
+        "find",
+        control(ControlEvent::Find),
+        "Search the file incrementally",
+    ),
+    command("goto", control(ControlEvent::Goto), "Go to a line"),
+    command(
+        "find-file",
+        control(ControlEvent::FindFile),
+        "Open a file of the project by fuzzy search",
+    ),
+    command(
+        "browse",
+        control(ControlEvent::Browse),
+        "List the files of the directory of the file",
+    ),
+    command(
+        "grep",
+        control(ControlEvent::Grep),
+        "Search the files of the project for text or a /regex/",
+    ),
+    command(
+        "filter",
+        control(ControlEvent::Filter),
+        "Filter the selected lines or the whole file through a shell command",
+    ),
+    command(
+        "build",
+        control(ControlEvent::Build),
+        "Run the build command and list its errors",
+    ),
+    command(
+        "quickfix",
+        control(ControlEvent::QuickFixList),
+        "List the errors of the last build or the matches of the last grep",
+    ),
+    command(
+        "next-error",
+        control(ControlEvent::NextError),
+        "Go to the next error or match",
+    ),
+    command(
+        "previous-error",
+        control(ControlEvent::PreviousError),
+        "Go to the previous error or match",
+    ),
+    command("help", control(ControlEvent::Help), "Show the key bindings"),
+    command(
+        "command-line",
+        control(ControlEvent::CommandLine),
+        "Run a command like 'w file', 'set ts=4' or '%s/a/b/g'",
+    ),
+    command("escape", control(ControlEvent::Escape), "Clear the message"),
+    command(
+        "toggle-line-numbers",
+        control(ControlEvent::Line),
+        "Show or hide line numbers",
+    ),
+    command(
+        "next-theme",
+        control(ControlEvent::Theme),
+        "Switch to the next color theme",
+    ),
+    command(
+        "set-syntax",
+        control(ControlEvent::Syntax),
+        "Set the syntax by name",
+    ),
+    command(
+        "toggle-auto-pairs",
+        control(ControlEvent::AutoPairs),
+        "Close brackets and quotes automatically",
+    ),
+    command("undo", control(ControlEvent::Undo), "Undo the last change"),
+    command(
+        "redo",
+        control(ControlEvent::Redo),
+        "Redo the last undone change",
+    ),
+    command(
+        "toggle-comment",
+        control(ControlEvent::ToggleComment),
+        "Comment or uncomment lines",
+    ),
+    command(
+        "match-bracket",
+        control(ControlEvent::MatchBracket),
+        "Jump to the matching bracket",
+    ),
+    command(
+        "insert-tab",
+        EditorEvent::Key('\t'),
+        "Indent with a tab or spaces",
+    ),
+    command(
+        "dedent-line",
+        control(ControlEvent::Dedent),
+        "Dedent the line",
+    ),
+    command(
+        "indent-block",
+        control(ControlEvent::IndentBlock),
+        "Indent the selection or block",
+    ),
+    command(
+        "dedent-block",
+        control(ControlEvent::DedentBlock),
+        "Dedent the selection or block",
+    ),
+    command(
+        "duplicate-lines",
+        control(ControlEvent::DuplicateLines),
+        "Duplicate the line or selection",
+    ),
+    command(
+        "move-lines-up",
+        control(ControlEvent::MoveLinesUp),
+        "Move lines up",
+    ),
+    command(
+        "move-lines-down",
+        control(ControlEvent::MoveLinesDown),
+        "Move lines down",
+    ),
+    command(
+        "join-lines",
+        control(ControlEvent::JoinLines),
+        "Join the next line onto this one",
+    ),
+    command(
+        "delete-lines",
+        control(ControlEvent::DeleteLines),
+        "Delete the line or selection",
+    ),
+    command(
+        "recenter",
+        control(ControlEvent::Recenter),
+        "Show the line of the cursor in the middle",
+    ),
+    command(
+        "set-mark",
+        control(ControlEvent::SetMark),
+        "Start a region at the cursor",
+    ),
+    command(
+        "kill-line",
+        control(ControlEvent::KillLine),
+        "Cut to the end of the line",
+    ),
+    command(
+        "kill-region",
+        control(ControlEvent::KillRegion),
+        "Cut the region",
+    ),
+    command(
+        "copy-region",
+        control(ControlEvent::CopyRegion),
+        "Copy the region",
+    ),
+    command(
+        "yank",
+        control(ControlEvent::Yank),
+        "Paste the last cut text",
+    ),
+    command(
+        "yank-pop",
+        control(ControlEvent::YankPop),
+        "Replace the pasted text with older cut text",
+    ),
+    command(
+        "record-macro",
+        control(ControlEvent::RecordMacro),
+        "Start or stop recording a macro",
+    ),
+    command(
+        "play-macro",
+        control(ControlEvent::PlayMacro),
+        "Play a macro a number of times, to the end of the file or until it fails",
+    ),
+    command(
+        "save-macro",
+        control(ControlEvent::SaveMacro),
+        "Save a macro for later sessions",
+    ),
+    command("left", cursor(CursorKey::Left), "Move left"),
+    command("right", cursor(CursorKey::Right), "Move right"),
+    command("up", cursor(CursorKey::Up), "Move up"),
+    command("down", cursor(CursorKey::Down), "Move down"),
+    command("page-up", cursor(CursorKey::PageUp), "Move a page up"),
+    command("page-down", cursor(CursorKey::PageDown), "Move a page down"),
+    command(
+        "line-start",
+        cursor(CursorKey::Home),
+        "Move to the start of the line",
+    ),
+    command(
+        "line-end",
+        cursor(CursorKey::End),
+        "Move to the end of the line",
+    ),
+    command("word-left", cursor(CursorKey::WordLeft), "Move a word left"),
+    command(
+        "word-right",
+        cursor(CursorKey::WordRight),
+        "Move a word right",
+    ),
+    command(
+        "file-start",
+        cursor(CursorKey::FileStart),
+        "Move to the start of the file",
+    ),
+    command(
+        "file-end",
+        cursor(CursorKey::FileEnd),
+        "Move to the end of the file",
+    ),
+    command(
+        "delete",
+        cursor(CursorKey::Delete),
+        "Delete the character at the cursor",
+    ),
+    command(
+        "backspace",
+        cursor(CursorKey::Backspace),
+        "Delete the character left of the cursor",
+    ),
+    command(
+        "delete-word-left",
+        cursor(CursorKey::DeleteWordLeft),
+        "Delete the word left of the cursor",
+    ),
+    command(
+        "delete-word-right",
+        cursor(CursorKey::DeleteWordRight),
+        "Delete the word right of the cursor",
+    ),
+    command("newline", cursor(CursorKey::Enter), "Insert a new line"),
+    command(
+        "select-left",
+        select(CursorKey::Left),
+        "Extend the selection left",
+    ),
+    command(
+        "select-right",
+        select(CursorKey::Right),
+        "Extend the selection right",
+    ),
+    command(
+        "select-up",
+        select(CursorKey::Up),
+        "Extend the selection up",
+    ),
+    command(
+        "select-down",
+        select(CursorKey::Down),
+        "Extend the selection down",
+    ),
+    command(
+        "select-page-up",
+        select(CursorKey::PageUp),
+        "Extend the selection a page up",
+    ),
+    command(
+        "select-page-down",
+        select(CursorKey::PageDown),
+        "Extend the selection a page down",
+    ),
+    command(
+        "select-line-start",
+        select(CursorKey::Home),
+        "Extend the selection to the line start",
+    ),
+    command(
+        "select-line-end",
+        select(CursorKey::End),
+        "Extend the selection to the line end",
+    ),
+    command(
+        "select-word-left",
+        select(CursorKey::WordLeft),
+        "Extend the selection a word left",
+    ),
+    command(
+        "select-word-right",
+        select(CursorKey::WordRight),
+        "Extend the selection a word right",
+    ),
+    command(
+        "select-file-start",
+        select(CursorKey::FileStart),
+        "Extend the selection to the file start",
+    ),
+    command(
+        "select-file-end",
+        select(CursorKey::FileEnd),
+        "Extend the selection to the file end",
+    ),
+];
+
+// Default key bindings, the same notation as keymap files
+pub const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("Ctrl-Q", "quit"),
+    ("Ctrl-S", "save"),
+    ("Ctrl-F", "find"),
+    ("Ctrl-G", "goto"),
+    ("Ctrl-O", "find-file"),
+    ("Ctrl-E", "browse"),
+    ("Alt-F", "grep"),
+    ("Alt-|", "filter"),
+    ("F5", "build"),
+    ("Shift-F5", "quickfix"),
+    ("F8", "next-error"),
+    ("Shift-F8", "previous-error"),
+    ("F1", "help"),
+    ("Ctrl-P", "command-line"),
+    ("Esc", "escape"),
+    ("F3", "record-macro"),
+    ("F4", "play-macro"),
+    ("Shift-F4", "save-macro"),
+    ("Ctrl-L", "toggle-line-numbers"),
+    ("Ctrl-T", "next-theme"),
+    ("Ctrl-Y", "set-syntax"),
+    ("Alt-P", "toggle-auto-pairs"),
+    ("Ctrl-Z", "undo"),
+    ("Ctrl-R", "redo"),
+    ("Ctrl-/", "toggle-comment"),
+    ("Ctrl-K Ctrl-C", "toggle-comment"),
+    ("Ctrl-B", "match-bracket"),
+    ("Tab", "insert-tab"),
+    ("Shift-Tab", "dedent-line"),
+    ("Alt-Right", "indent-block"),
+    ("Alt-Left", "dedent-block"),
+    ("Ctrl-D", "duplicate-lines"),
+    ("Alt-Up", "move-lines-up"),
+    ("Alt-Down", "move-lines-down"),
+    ("Alt-J", "join-lines"),
+    ("Alt-K", "delete-lines"),
+    ("Left", "left"),
+    ("Right", "right"),
+    ("Up", "up"),
+    ("Down", "down"),
+    ("PageUp", "page-up"),
+    ("PageDown", "page-down"),
+    ("Home", "line-start"),
+    ("End", "line-end"),
+    ("Ctrl-Left", "word-left"),
+    ("Ctrl-Right", "word-right"),
+    ("Ctrl-Home", "file-start"),
+    ("Ctrl-End", "file-end"),
+    ("Delete", "delete"),
+    ("Backspace", "backspace"),
+    ("Ctrl-H", "backspace"),
+    ("Ctrl-Backspace", "delete-word-left"),
+    ("Alt-Backspace", "delete-word-left"),
+    ("Ctrl-Delete", "delete-word-right"),
+    ("Enter", "newline"),
+    ("Shift-Left", "select-left"),
+    ("Shift-Right", "select-right"),
+    ("Shift-Up", "select-up"),
+    ("Shift-Down", "select-down"),
+    ("Shift-PageUp", "select-page-up"),
+    ("Shift-PageDown", "select-page-down"),
+    ("Shift-Home", "select-line-start"),
+    ("Shift-End", "select-line-end"),
+    ("Ctrl-Shift-Left", "select-word-left"),
+    ("Ctrl-Shift-Right", "select-word-right"),
+    ("Ctrl-Shift-Home", "select-file-start"),
+    ("Ctrl-Shift-End", "select-file-end"),
+];
+
+/*
+ * Bindings of the emacs editing mode on top of the default ones. Keys of
+ * the defaults that emacs uses differently, like Ctrl-F and Ctrl-S, are
+ * taken over and the commands bound to them move to other keys.
+ */
+pub const EMACS_BINDINGS: &[(&str, &str)] = &[
+    ("Ctrl-A", "line-start"),
+    ("Ctrl-E", "line-end"),
+    ("Ctrl-N", "down"),
+    ("Ctrl-P", "up"),
+    ("Ctrl-F", "right"),
+    ("Ctrl-B", "left"),
+    ("Alt-F", "word-right"),
+    ("Alt-B", "word-left"),
+    ("Ctrl-V", "page-down"),
+    ("Alt-V", "page-up"),
+    ("Alt-<", "file-start"),
+    ("Alt->", "file-end"),
+    ("Ctrl-D", "delete"),
+    ("Alt-D", "delete-word-right"),
+    ("Ctrl-K", "kill-line"),
+    ("Ctrl-K Ctrl-C", "none"),
+    ("Ctrl-W", "kill-region"),
+    ("Alt-W", "copy-region"),
+    ("Ctrl-Y", "yank"),
+    ("Alt-Y", "yank-pop"),
+    ("Ctrl-Space", "set-mark"),
+    ("Ctrl-G", "escape"),
+    ("Ctrl-S", "find"),
+    ("Ctrl-R", "find"),
+    ("Ctrl-/", "undo"),
+    ("Alt-_", "redo"),
+    ("Ctrl-H", "help"),
+    ("Alt-X", "command-line"),
+    ("Ctrl-L", "recenter"),
+    ("Alt-;", "toggle-comment"),
+    ("Ctrl-Alt-F", "match-bracket"),
+    ("Ctrl-X Ctrl-S", "save"),
+    ("Ctrl-X Ctrl-C", "quit"),
+    ("Ctrl-X Ctrl-F", "find-file"),
+    ("Ctrl-X D", "browse"),
+    ("Alt-S G", "grep"),
+    ("Alt-G N", "next-error"),
+    ("Alt-G Alt-N", "next-error"),
+    ("Alt-G P", "previous-error"),
+    ("Alt-G Alt-P", "previous-error"),
+    ("Alt-G G", "goto"),
+    ("Alt-G Alt-G", "goto"),
+    ("Ctrl-X L", "toggle-line-numbers"),
+    ("Ctrl-X T", "next-theme"),
+    ("Ctrl-X Y", "set-syntax"),
+    ("Ctrl-X (", "record-macro"),
+    ("Ctrl-X )", "record-macro"),
+    ("Ctrl-X E", "play-macro"),
+];
+
+pub fn find_command(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+pub fn command_for_event(event: EditorEvent) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.event == event)
+}
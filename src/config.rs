@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path;
+
+use crate::detect::*;
+use crate::syntax::*;
+
+const CONFIG_FILE: &str = "config";
+
+/*
+ * Path of a file in the configuration directory, which is
+ * $XDG_CONFIG_HOME/kilo-rs or ~/.config/kilo-rs.
+ */
+pub fn config_path(name: &str) -> Option<path::PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => path::PathBuf::from(dir),
+        _ => path::PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("kilo-rs").join(name))
+}
+
+/*
+ * Split the lines of a configuration file into 'key = value' pairs along
+ * with their line numbers. Empty lines and lines starting with '#' are
+ * skipped, lines without a '=' are returned as errors.
+ */
+pub fn parse_settings(text: &str) -> Vec<Result<(usize, &str, &str), String>> {
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(lno, line)| match line.split_once('=') {
+            Some((key, value)) => Ok((lno, key.trim(), value.trim())),
+            None => Err(format!("line {}: expected 'key = value'", lno)),
+        })
+        .collect()
+}
+
+// How keys are interpreted, set with 'editing-mode' in the config file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EditingMode {
+    Default,
+    Vi,
+    Emacs,
+}
+
+// Command run by the build command when none is set
+const DEFAULT_BUILD_COMMAND: &str = "cargo build";
+
+pub struct Config {
+    pub editing_mode: EditingMode,
+    pub build_command: String,
+    pub format_on_save: bool,
+    formatters: HashMap<String, String>, // commands by syntax name, empty for none
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self {
+            editing_mode: EditingMode::Default,
+            build_command: DEFAULT_BUILD_COMMAND.to_string(),
+            format_on_save: false,
+            formatters: HashMap::new(),
+        }
+    }
+
+    // The formatter of a syntax, set with 'formatter.rust = ...' or else its usual one
+    pub fn formatter(&self, syntax: &Syntax) -> Option<String> {
+        match self
+            .formatters
+            .get(&syntax.filetype.to_string().to_lowercase())
+        {
+            Some(command) if command.is_empty() => None,
+            Some(command) => Some(command.clone()),
+            None => syntax.formatter.map(String::from),
+        }
+    }
+
+    /*
+     * Read the config file of the user, $XDG_CONFIG_HOME/kilo-rs/config.
+     * Return the settings along with the errors in the file.
+     */
+    pub fn load() -> (Self, Vec<String>) {
+        let mut config = Self::new();
+        let path = match config_path(CONFIG_FILE) {
+            Some(path) if path.exists() => path,
+            _ => return (config, Vec::new()),
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => return (config, vec![format!("{}: {}", path.display(), e)]),
+        };
+        let errors = parse_settings(&text)
+            .into_iter()
+            .filter_map(|setting| match setting {
+                Ok((lno, key, value)) => config
+                    .set(key, value)
+                    .err()
+                    .map(|e| format!("config line {}: {}", lno, e)),
+                Err(e) => Some(format!("config {}", e)),
+            })
+            .collect();
+        (config, errors)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "editing-mode" => {
+                self.editing_mode = match value {
+                    "default" => EditingMode::Default,
+                    "vi" => EditingMode::Vi,
+                    "emacs" => EditingMode::Emacs,
+                    _ => return Err(format!("unknown editing mode '{}'", value)),
+                }
+            }
+            "build-command" if value.is_empty() => return Err("empty build command".to_string()),
+            "build-command" => self.build_command = value.to_string(),
+            "format-on-save" => {
+                self.format_on_save = match value {
+                    "yes" | "true" => true,
+                    "no" | "false" => false,
+                    _ => return Err(format!("expected yes or no, not '{}'", value)),
+                }
+            }
+            _ => match key.strip_prefix("formatter.").map(find_syntax) {
+                Some(Some(syntax)) => {
+                    let name = syntax.filetype.to_string().to_lowercase();
+                    self.formatters.insert(name, value.to_string());
+                }
+                Some(None) => return Err(format!("unknown syntax in '{}'", key)),
+                None => return Err(format!("unknown setting '{}'", key)),
+            },
+        }
+        Ok(())
+    }
+}
@@ -8,19 +8,20 @@ pub struct EditRow {
     pub render: String,            // characters rendered on the screen
     pub highlight: Vec<Highlight>, // highlight for each character in 'render'
     pub open_ml_comment: bool,     // If current row has an open multiline comment
+    pub tabstop: u16,              // number of columns a tab is rendered with
 }
 
-const TABSTOP: u16 = 8;
+pub const TABSTOP: u16 = 8;
 
 impl EditRow {
-    fn render_chars(chars: &str) -> String {
+    fn render_chars(chars: &str, tabstop: u16) -> String {
         let mut idx = 0;
         let mut render = String::new();
         for c in chars.chars() {
             if c == '\t' {
                 render.push(' ');
                 idx += 1;
-                while idx % TABSTOP != 0 {
+                while idx % tabstop != 0 {
                     render.push(' ');
                     idx += 1;
                 }
@@ -33,21 +34,27 @@ impl EditRow {
     }
 
     pub fn update_row(&mut self) {
-        self.render = Self::render_chars(&self.chars);
+        self.render = Self::render_chars(&self.chars, self.tabstop);
         self.highlight = vec![Highlight::Normal; self.render.len()];
     }
 
-    pub fn new(chars: String, open_comment: bool) -> Self {
+    pub fn new(chars: String, open_comment: bool, tabstop: u16) -> Self {
         let mut newrow = Self {
             chars,
             render: String::new(),
             highlight: Vec::new(),
             open_ml_comment: open_comment,
+            tabstop,
         };
         newrow.update_row();
         newrow
     }
 
+    pub fn set_tabstop(&mut self, tabstop: u16) {
+        self.tabstop = tabstop;
+        self.update_row();
+    }
+
     /* Loop through all the characters to the left of cx to figure out how
      * many spaces each tab takes. For each character, if it's a tab, use
      * rx % TAB_STOP to find out how many columns we are is to the right
@@ -63,7 +70,7 @@ impl EditRow {
         let mut rx = 0;
         for c in self.chars.chars().take(cx as usize) {
             if c == '\t' {
-                rx += (TABSTOP - 1) - (rx % TABSTOP);
+                rx += (self.tabstop - 1) - (rx % self.tabstop);
             }
             rx += 1;
         }
@@ -84,7 +91,7 @@ impl EditRow {
         let mut cx = 0;
         for c in self.chars.chars() {
             if c == '\t' {
-                cur_rx += (TABSTOP - 1) - (cur_rx % TABSTOP);
+                cur_rx += (self.tabstop - 1) - (cur_rx % self.tabstop);
             }
             cur_rx += 1;
             if cur_rx > rx {
@@ -105,6 +112,11 @@ impl EditRow {
         self.update_row();
     }
 
+    pub fn insert_str(&mut self, idx: usize, s: &str) {
+        self.chars.insert_str(idx, s);
+        self.update_row();
+    }
+
     pub fn delete_char(&mut self, idx: usize) {
         if idx >= self.chars.len() {
             return;
@@ -117,7 +129,44 @@ impl EditRow {
     pub fn split(&mut self, at: usize) -> Self {
         let right = self.chars.split_off(at);
         self.update_row();
-        Self::new(right, self.open_ml_comment)
+        Self::new(right, self.open_ml_comment, self.tabstop)
+    }
+
+    // The text before and after the character at 'at'
+    pub fn split_at_char(&self, at: usize) -> (&str, &str) {
+        let at = self
+            .chars
+            .char_indices()
+            .nth(at)
+            .map_or(self.chars.len(), |(i, _)| i);
+        self.chars.split_at(at)
+    }
+
+    pub fn set_chars(&mut self, chars: &str) {
+        self.chars = chars.to_string();
+        self.update_row();
+    }
+
+    // Leading whitespace of the row
+    pub fn indentation(&self) -> &str {
+        let len = self.chars.len() - self.chars.trim_start().len();
+        &self.chars[..len]
+    }
+
+    // Text of the row before 'at' without the trailing comment and whitespace
+    pub fn code(&self, at: usize) -> String {
+        let code: String = self
+            .chars
+            .chars()
+            .take(at)
+            .enumerate()
+            .filter(|&(cx, _)| {
+                let rx = self.cx_to_rx(cx as u16) as usize;
+                self.highlight.get(rx) != Some(&Highlight::Comment)
+            })
+            .map(|(_, c)| c)
+            .collect();
+        code.trim_end().to_string()
     }
 
     fn get_render_range(&self, offset: usize, len: usize) -> &str {
@@ -133,43 +182,66 @@ impl EditRow {
         self.highlight.fill(Highlight::Normal);
         let mut i = 0;
         let mut prev_sep = true;
-        // could be any one of [", ', \0]
+        // could be any one of [", ', `, \0]
         let mut in_string: char = '\0';
+        let mut string_start = 0;
+        // If inside a markup tag such as <a href="...">
+        let mut in_tag = false;
         // Get the 'in_ml_comment' state of the previous row
         let mut in_ml_comment = state.prev_in_ml_comment;
         state.ml_comment_changed = false;
+        // Fenced code blocks of markdown are tracked like multiline comments
+        let ml_hl = match syntax {
+            Some(syntax) if syntax.flags & MARKDOWN != 0 => Highlight::Code,
+            _ => Highlight::Comment,
+        };
+        // Span of a bare key at the start of the line, as in 'key: value'
+        let mut key = None;
+
+        if let Some(syntax) = syntax {
+            if syntax.flags & DIFF != 0 {
+                self.highlight_diff_line();
+                i = render_chars.len();
+            } else if !in_ml_comment {
+                if syntax.flags & MARKDOWN != 0 {
+                    i = self.highlight_markdown_line(&render_chars);
+                }
+                if syntax.flags & SECTIONS != 0 && Self::is_section(&self.render) {
+                    self.highlight.fill(Highlight::Heading);
+                    i = render_chars.len();
+                }
+                if syntax.flags & KEYS != 0 {
+                    key = Self::bare_key(&render_chars);
+                }
+            }
+        }
 
         'outer: while i < render_chars.len() {
             let c = render_chars[i];
-            let prev_hl = if i > 0 {
-                self.highlight[i - 1]
-            } else {
-                Highlight::Normal
-            };
 
             #[allow(clippy::collapsible_if)]
             if let Some(syntax) = syntax {
-                /* Highlight single line comments.
-                 * Ignore single line comments within a multiline comment
-                 */
-                for single in &syntax.comment.single {
-                    let s = self.get_render_range(i, single.len());
-                    if in_string == '\0' && s == single && !in_ml_comment {
-                        // Highlight the rest of the line
-                        self.highlight[i..self.render.len()].fill(Highlight::Comment);
-                        break;
+                if let Some((start, len)) = key {
+                    if i == start {
+                        self.highlight[i..i + len].fill(Highlight::Key);
+                        i += len;
+                        prev_sep = false;
+                        continue;
                     }
                 }
 
-                // Multiline comments
+                /* Multiline comments. These are checked before single line comments
+                 * since the start of a multiline comment can look like a single line
+                 * comment, e.g. '--[[' and '--' in Lua.
+                 */
                 if let Some((mcs, mce)) = &syntax.comment.multiline {
                     if in_string == '\0' {
                         if in_ml_comment {
                             // Safely highlight the current character
-                            self.highlight[i] = Highlight::Comment;
+                            self.highlight[i] = ml_hl;
                             let s = self.get_render_range(i, mce.len());
                             if s == mce {
-                                self.highlight[i..i + mce.len()].fill(Highlight::Comment);
+                                self.highlight[i..i + mce.len()].fill(ml_hl);
                                 i += mce.len();
                                 in_ml_comment = false;
                                 prev_sep = true;
@@ -181,7 +253,7 @@ impl EditRow {
                         } else {
                             let s = self.get_render_range(i, mcs.len());
                             if s == mcs {
-                                self.highlight[i..i + mcs.len()].fill(Highlight::Comment);
+                                self.highlight[i..i + mcs.len()].fill(ml_hl);
                                 i += mcs.len();
                                 in_ml_comment = true;
                                 continue;
@@ -190,19 +262,89 @@ impl EditRow {
                     }
                 }
 
-                // Highlight numbers
-                if syntax.flags & NUMBERS != 0 {
-                    if (c.is_ascii_digit() && (prev_sep || prev_hl == Highlight::Number))
-                        || (c == '.' && prev_hl == Highlight::Number)
-                    {
-                        self.highlight[i] = Highlight::Number;
-                        i += 1;
+                /* Highlight single line comments.
+                 * Ignore single line comments within a multiline comment
+                 */
+                for single in &syntax.comment.single {
+                    let s = self.get_render_range(i, single.len());
+                    if in_string == '\0' && s == single && !in_ml_comment {
+                        // Highlight the rest of the line
+                        self.highlight[i..self.render.len()].fill(Highlight::Comment);
+                        break 'outer;
+                    }
+                }
+
+                // Code spans, emphasis and links of markdown
+                if syntax.flags & MARKDOWN != 0 {
+                    if let Some(len) = self.highlight_markdown_span(&render_chars, i) {
+                        i += len;
+                        prev_sep = true;
+                        continue;
+                    }
+                }
+
+                // Markup tags, their attributes and the closing '>'
+                if syntax.flags & TAGS != 0 && in_string == '\0' {
+                    if in_tag {
+                        if c == '>' {
+                            self.highlight[i] = Highlight::KeywordBase;
+                            in_tag = false;
+                            i += 1;
+                            prev_sep = true;
+                            continue;
+                        }
+                        let len = Self::word_len(&render_chars[i..]);
+                        if len > 0 && prev_sep {
+                            if render_chars.get(i + len) == Some(&'=') {
+                                self.highlight[i..i + len].fill(Highlight::Key);
+                            }
+                            i += len;
+                            prev_sep = false;
+                            continue;
+                        }
+                    } else if c == '<' {
+                        let mut len = 1;
+                        if matches!(render_chars.get(i + 1), Some('/') | Some('!') | Some('?')) {
+                            len += 1;
+                        }
+                        let name_len = Self::word_len(&render_chars[i + len..]);
+                        if name_len > 0 {
+                            len += name_len;
+                            self.highlight[i..i + len].fill(Highlight::KeywordBase);
+                            in_tag = true;
+                            i += len;
+                            prev_sep = true;
+                            continue;
+                        }
+                    }
+                }
+
+                /* Highlight numbers. A number can only start where an identifier
+                 * does not continue, so the digit in 'x1' is left alone. If the
+                 * word turns out not to be a valid literal, skip all of it.
+                 */
+                if syntax.flags & NUMBERS != 0 && in_string == '\0' {
+                    let starts_number = c.is_ascii_digit()
+                        || (c == '.'
+                            && matches!(render_chars.get(i + 1), Some(n) if n.is_ascii_digit()));
+                    if starts_number && (i == 0 || !Number::is_ident_char(render_chars[i - 1])) {
+                        let len = syntax.number.literal_len(&render_chars[i..]);
+                        if len > 0 {
+                            self.highlight[i..i + len].fill(Highlight::Number);
+                            i += len;
+                        } else {
+                            while i < render_chars.len() && Number::is_ident_char(render_chars[i]) {
+                                i += 1;
+                            }
+                        }
                         prev_sep = false;
                         continue;
                     }
                 }
 
-                // Highlight strings
+                /* Highlight strings. In markup, quotes only start strings inside
+                 * of tags, so that apostrophes in the text are left alone.
+                 */
                 if syntax.flags & STRINGS != 0 {
                     if in_string != '\0' {
                         self.highlight[i] = Highlight::Str;
@@ -212,13 +354,23 @@ impl EditRow {
                         } else {
                             if c == in_string {
                                 in_string = '\0';
+                                // A quoted key, as in '"key": value'
+                                let next =
+                                    render_chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                                if syntax.flags & KEYS != 0 && matches!(next, Some(':') | Some('='))
+                                {
+                                    self.highlight[string_start..=i].fill(Highlight::Key);
+                                }
                             }
                             prev_sep = true;
                         }
                         i += 1;
                         continue;
-                    } else if c == '"' || c == '\'' {
+                    } else if (c == '"' || c == '\'' || (c == '`' && syntax.flags & BACKTICKS != 0))
+                        && (syntax.flags & TAGS == 0 || in_tag)
+                    {
                         in_string = c;
+                        string_start = i;
                         self.highlight[i] = Highlight::Str;
                         i += 1;
                         continue;
@@ -243,7 +395,13 @@ impl EditRow {
                                     true
                                 };
 
-                            if &self.render[i..i + kw_name.len()] == kw_name && is_last_sep {
+                            let word = self.get_render_range(i, kw_len);
+                            let is_match = if syntax.flags & IGNORE_CASE != 0 {
+                                word.eq_ignore_ascii_case(kw_name)
+                            } else {
+                                word == kw_name
+                            };
+                            if is_match && is_last_sep {
                                 self.highlight[i..i + kw_name.len()].fill(kw_hl);
                                 i += kw_name.len();
                                 prev_sep = false;
@@ -260,20 +418,242 @@ impl EditRow {
 
         // If the row is in the middle of a multiline comment
         if in_ml_comment {
-            self.highlight.fill(Highlight::Comment);
+            self.highlight.fill(ml_hl);
         }
         state.ml_comment_changed = self.open_ml_comment != in_ml_comment;
         self.open_ml_comment = in_ml_comment;
     }
 
+    /*
+     * Check if a character separates words. Beyond ASCII, whitespace and
+     * anything that is not a letter or a digit, like '«' or '。', does.
+     */
     pub fn is_separator(ch: char) -> bool {
+        if !ch.is_ascii() {
+            return !ch.is_alphanumeric();
+        }
         ch.is_ascii_whitespace()
             || [
                 ',', '.', '(', ')', '+', '-', '*', '/', '=', '~', '%', '<', '>', '[', ']', ';',
+                '{', '}', ':', '!', '&', '|', '^', '?', '"', '\'',
             ]
             .contains(&ch)
     }
 
+    /*
+     * Index of the start of the word left of 'cx'. Whitespace is skipped
+     * first, then a run of word characters or a run of other separators.
+     */
+    pub fn word_left(&self, cx: usize) -> usize {
+        let chars: Vec<char> = self.chars.chars().collect();
+        Self::word_left_of(&chars, cx)
+    }
+
+    // word_left() in any characters, like those typed at a prompt
+    pub fn word_left_of(chars: &[char], cx: usize) -> usize {
+        let mut cx = cx.min(chars.len());
+        while cx > 0 && chars[cx - 1].is_whitespace() {
+            cx -= 1;
+        }
+        if cx > 0 {
+            let sep = Self::is_separator(chars[cx - 1]);
+            while cx > 0
+                && !chars[cx - 1].is_whitespace()
+                && Self::is_separator(chars[cx - 1]) == sep
+            {
+                cx -= 1;
+            }
+        }
+        cx
+    }
+
+    // Index of the start of the word right of 'cx', after the whitespace following the current one
+    pub fn word_right(&self, cx: usize) -> usize {
+        let chars: Vec<char> = self.chars.chars().collect();
+        Self::word_right_of(&chars, cx)
+    }
+
+    pub fn word_right_of(chars: &[char], cx: usize) -> usize {
+        let mut cx = cx.min(chars.len());
+        if cx < chars.len() && !chars[cx].is_whitespace() {
+            let sep = Self::is_separator(chars[cx]);
+            while cx < chars.len()
+                && !chars[cx].is_whitespace()
+                && Self::is_separator(chars[cx]) == sep
+            {
+                cx += 1;
+            }
+        }
+        while cx < chars.len() && chars[cx].is_whitespace() {
+            cx += 1;
+        }
+        cx
+    }
+
+    // Index of the last character of the first word ending at or after 'from', if any
+    pub fn word_end(&self, from: usize) -> Option<usize> {
+        let chars: Vec<char> = self.chars.chars().collect();
+        let mut cx = from;
+        while cx < chars.len() && chars[cx].is_whitespace() {
+            cx += 1;
+        }
+        if cx >= chars.len() {
+            return None;
+        }
+        let sep = Self::is_separator(chars[cx]);
+        while cx + 1 < chars.len()
+            && !chars[cx + 1].is_whitespace()
+            && Self::is_separator(chars[cx + 1]) == sep
+        {
+            cx += 1;
+        }
+        Some(cx)
+    }
+
+    // Length of the word made of identifier characters and dashes at the start of 'chars'
+    fn word_len(chars: &[char]) -> usize {
+        chars
+            .iter()
+            .take_while(|&&c| Number::is_ident_char(c) || c == '-' || c == '.')
+            .count()
+    }
+
+    /*
+     * Check if what follows a key is a key/value separator, i.e. ':' followed
+     * by a space or the end of the line, '=' or assignments like ':=' or '+='.
+     */
+    fn is_key_end(chars: &[char]) -> bool {
+        let rest: Vec<char> = chars
+            .iter()
+            .skip_while(|c| c.is_whitespace())
+            .take(2)
+            .copied()
+            .collect();
+        match rest.as_slice() {
+            [':'] | ['='] | ['=', _] => true,
+            [':', next] => next.is_whitespace() || *next == '=',
+            [c, '='] => ['?', '+'].contains(c),
+            _ => false,
+        }
+    }
+
+    // Span of a key at the start of a line like 'key: value', 'key = value' or '- key: value'
+    fn bare_key(chars: &[char]) -> Option<(usize, usize)> {
+        let mut start = chars.iter().take_while(|c| c.is_whitespace()).count();
+        // Keys of a YAML list item
+        if chars.get(start) == Some(&'-') && chars.get(start + 1) == Some(&' ') {
+            start += 2;
+            start += chars[start..]
+                .iter()
+                .take_while(|c| c.is_whitespace())
+                .count();
+        }
+        let len = Self::word_len(&chars[start..]);
+        if len > 0 && Self::is_key_end(&chars[start + len..]) {
+            Some((start, len))
+        } else {
+            None
+        }
+    }
+
+    // Section headers of TOML and INI files like '[package]' or '[[bin]]'
+    fn is_section(line: &str) -> bool {
+        let line = line.trim();
+        line.len() > 2 && line.starts_with('[') && line.ends_with(']') && !line.contains('=')
+    }
+
+    // Lines of a diff: file headers, hunk headers, added and removed lines
+    fn highlight_diff_line(&mut self) {
+        let hl = if ["diff ", "index ", "+++", "---", "@@"]
+            .iter()
+            .any(|p| self.render.starts_with(p))
+        {
+            Highlight::DiffHeader
+        } else if self.render.starts_with('+') {
+            Highlight::DiffAdd
+        } else if self.render.starts_with('-') {
+            Highlight::DiffDelete
+        } else {
+            Highlight::Normal
+        };
+        self.highlight.fill(hl);
+    }
+
+    /*
+     * Highlight the markdown constructs that depend on the start of the line.
+     * Headings and block quotes take the whole line, list markers only the
+     * marker itself. Return the index where the rest of the line starts.
+     */
+    fn highlight_markdown_line(&mut self, chars: &[char]) -> usize {
+        let indent = chars.iter().take_while(|c| c.is_whitespace()).count();
+        let rest = &chars[indent..];
+        let hashes = rest.iter().take_while(|&&c| c == '#').count();
+
+        if (1..=6).contains(&hashes) && rest.get(hashes).is_none_or(|c| c.is_whitespace()) {
+            self.highlight.fill(Highlight::Heading);
+            return chars.len();
+        }
+        if rest.first() == Some(&'>') {
+            self.highlight.fill(Highlight::Comment);
+            return chars.len();
+        }
+        let marker = match rest {
+            ['-' | '*' | '+', ' ', ..] => 1,
+            _ => {
+                let digits = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+                if digits > 0 && matches!(rest.get(digits), Some('.') | Some(')')) {
+                    digits + 1
+                } else {
+                    0
+                }
+            }
+        };
+        self.highlight[indent..indent + marker].fill(Highlight::KeywordBase);
+        indent + marker
+    }
+
+    /*
+     * Highlight a markdown code span, emphasis or link starting at 'i' and
+     * return its length, or None if there is none. Spans have to be closed
+     * on the same line.
+     */
+    fn highlight_markdown_span(&mut self, chars: &[char], i: usize) -> Option<usize> {
+        let c = chars[i];
+        let find =
+            |from: usize, pat: &[char]| (from..chars.len()).find(|&j| chars[j..].starts_with(pat));
+        match c {
+            '`' => {
+                let ticks = chars[i..].iter().take_while(|&&c| c == '`').count();
+                let fence = vec!['`'; ticks];
+                let end = find(i + ticks, &fence)? + ticks;
+                self.highlight[i..end].fill(Highlight::Code);
+                Some(end - i)
+            }
+            '*' | '_' => {
+                // Underscores inside of words like snake_case are not emphasis
+                if c == '_' && i > 0 && Number::is_ident_char(chars[i - 1]) {
+                    return None;
+                }
+                let run = chars[i..].iter().take_while(|&&ch| ch == c).count().min(3);
+                if chars.get(i + run).is_none_or(|c| c.is_whitespace()) {
+                    return None;
+                }
+                let marker = vec![c; run];
+                let end = find(i + run + 1, &marker)? + run;
+                self.highlight[i..end].fill(Highlight::Emphasis);
+                Some(end - i)
+            }
+            '[' => {
+                let close = find(i + 1, &[']', '('])?;
+                let end = find(close + 2, &[')'])? + 1;
+                self.highlight[i..=close].fill(Highlight::Key);
+                self.highlight[close + 1..end].fill(Highlight::Str);
+                Some(end - i)
+            }
+            _ => None,
+        }
+    }
+
     pub fn highlight_match(&mut self, start: usize, len: usize) {
         for c in self.highlight[start..start + len].iter_mut() {
             *c = Highlight::Match
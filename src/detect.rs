@@ -0,0 +1,312 @@
+use std::cmp;
+use std::path;
+
+use crate::data::TABSTOP;
+use crate::syntax::*;
+
+// Number of lines at the start and end of a file that are checked for modelines
+const MODELINES: usize = 5;
+// Number of lines that are checked to detect the indentation of a file
+const INDENT_LINES: usize = 1000;
+
+// Indentation settings of a file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Indentation {
+    pub style: IndentStyle,
+    pub tabstop: u16,
+}
+
+/*
+ * Detect the syntax of a file. An explicit modeline wins over everything
+ * else, followed by the file name or a glob pattern of it, the extension
+ * and finally the interpreter on the shebang line.
+ */
+pub fn detect_syntax(filename: &str, lines: &[&str]) -> Option<&'static Syntax> {
+    let basename = path::Path::new(filename)
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    modeline_syntax(lines)
+        .or_else(|| pattern_syntax(&basename))
+        .or_else(|| extension_syntax(&basename))
+        .or_else(|| lines.first().and_then(|line| shebang_syntax(line)))
+}
+
+/*
+ * Look up a syntax by name, as used by modelines and the set syntax
+ * command. The name can be the file type, an extension or an interpreter.
+ */
+pub fn find_syntax(name: &str) -> Option<&'static Syntax> {
+    let name = name.trim().to_lowercase();
+    HLDB.iter().find(|syntax| {
+        syntax.filetype.to_string().to_lowercase() == name
+            || syntax.filematch.extensions.contains(&name)
+            || syntax.filematch.interpreters.contains(&name)
+    })
+}
+
+fn pattern_syntax(basename: &str) -> Option<&'static Syntax> {
+    HLDB.iter().find(|syntax| {
+        syntax
+            .filematch
+            .patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, basename))
+    })
+}
+
+/*
+ * Try the longest extension first, so that a pattern such as "tar.gz"
+ * can be told apart from "gz".
+ */
+fn extension_syntax(basename: &str) -> Option<&'static Syntax> {
+    let name = basename.trim_start_matches('.');
+    for (idx, _) in name.match_indices('.') {
+        let ext = &name[idx + 1..];
+        for syntax in HLDB.iter() {
+            if syntax.filematch.extensions.iter().any(|e| e == ext) {
+                return Some(syntax);
+            }
+        }
+    }
+    None
+}
+
+/*
+ * Find the interpreter of a shebang line such as '#!/bin/sh' or
+ * '#!/usr/bin/env -S python3 -u'. Version numbers at the end of the
+ * interpreter name, as in 'python3.11', are ignored.
+ */
+fn shebang_syntax(line: &str) -> Option<&'static Syntax> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        // Skip the options and variable assignments of env
+        interpreter = words.find(|w| !w.starts_with('-') && !w.contains('='))?;
+    }
+    let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    HLDB.iter().find(|syntax| {
+        syntax
+            .filematch
+            .interpreters
+            .iter()
+            .any(|i| i == interpreter)
+    })
+}
+
+/*
+ * Detect the indentation of a file. The default of the language is
+ * overridden by the indentation the lines of the file already use, and
+ * that by the options of a modeline.
+ */
+pub fn detect_indentation(lines: &[&str], syntax: Option<&Syntax>) -> Indentation {
+    let mut indentation = Indentation {
+        style: syntax.map_or(IndentStyle::Tabs, |s| s.indent.style),
+        tabstop: TABSTOP,
+    };
+    if let Some(style) = content_indentation(lines) {
+        indentation.style = style;
+    }
+    for line in modelines(lines) {
+        if let Some(options) = vim_modeline(line) {
+            vim_indentation(&options, &mut indentation);
+        } else if let Some(vars) = emacs_modeline(line) {
+            emacs_indentation(&vars, &mut indentation);
+        }
+    }
+    indentation
+}
+
+/*
+ * Lines indented with tabs or spaces are counted, whichever is used more
+ * often wins. The width of spaces is the most common change of the
+ * indentation between two lines.
+ */
+fn content_indentation(lines: &[&str]) -> Option<IndentStyle> {
+    let (mut tabs, mut spaces) = (0, 0);
+    let mut widths = [0; 9];
+    let mut prev = 0;
+
+    for line in lines.iter().take(INDENT_LINES) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with('\t') {
+            tabs += 1;
+            continue;
+        }
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        // Continuation lines of block comments are aligned with a single space
+        if line[indent..].starts_with('*') {
+            continue;
+        }
+        if indent > 0 {
+            spaces += 1;
+        }
+        let change = indent.abs_diff(prev);
+        if (2..widths.len()).contains(&change) {
+            widths[change] += 1;
+        }
+        prev = indent;
+    }
+
+    if tabs > spaces {
+        return Some(IndentStyle::Tabs);
+    }
+    // The smaller width wins a tie, as two levels of it look like one of the larger
+    let width = (2..widths.len()).max_by_key(|&w| (widths[w], cmp::Reverse(w)))?;
+    if spaces > 0 && widths[width] > 0 {
+        Some(IndentStyle::Spaces(width as u16))
+    } else {
+        None
+    }
+}
+
+// Options like 'et', 'noet', 'sw=4' and 'ts=8' of a vim modeline
+fn vim_indentation(options: &[&str], indentation: &mut Indentation) {
+    let mut expand = matches!(indentation.style, IndentStyle::Spaces(_));
+    let mut width = match indentation.style {
+        IndentStyle::Spaces(width) => width,
+        IndentStyle::Tabs => indentation.tabstop,
+    };
+    for option in options {
+        let (key, value) = option.split_once('=').unwrap_or((option, ""));
+        let value = value.parse::<u16>().ok().filter(|&v| v > 0);
+        match (key, value) {
+            ("et" | "expandtab", _) => expand = true,
+            ("noet" | "noexpandtab", _) => expand = false,
+            ("sw" | "shiftwidth" | "sts" | "softtabstop", Some(value)) => width = value,
+            ("ts" | "tabstop", Some(value)) => indentation.tabstop = value,
+            _ => continue,
+        }
+    }
+    indentation.style = if expand {
+        IndentStyle::Spaces(width)
+    } else {
+        IndentStyle::Tabs
+    };
+}
+
+// Variables like 'indent-tabs-mode: nil', 'tab-width: 8' and 'c-basic-offset: 4'
+fn emacs_indentation(vars: &[(&str, &str)], indentation: &mut Indentation) {
+    for &(key, value) in vars {
+        let number = value.parse::<u16>().ok().filter(|&v| v > 0);
+        match (key, number) {
+            ("indent-tabs-mode", _) if value == "nil" => {
+                if indentation.style == IndentStyle::Tabs {
+                    indentation.style = IndentStyle::Spaces(indentation.tabstop);
+                }
+            }
+            ("indent-tabs-mode", _) => indentation.style = IndentStyle::Tabs,
+            ("tab-width", Some(width)) => indentation.tabstop = width,
+            (key, Some(width)) if key.ends_with("-offset") || key.ends_with("-indent-level") => {
+                if let IndentStyle::Spaces(_) = indentation.style {
+                    indentation.style = IndentStyle::Spaces(width);
+                }
+            }
+            _ => continue,
+        }
+    }
+}
+
+fn modeline_syntax(lines: &[&str]) -> Option<&'static Syntax> {
+    modelines(lines)
+        .find_map(|line| {
+            vim_modeline(line)
+                .and_then(|options| {
+                    options.iter().find_map(|option| {
+                        let (key, value) = option.split_once('=')?;
+                        let is_syntax = ["ft", "filetype", "syn", "syntax"].contains(&key);
+                        (is_syntax && !value.is_empty()).then_some(value)
+                    })
+                })
+                .or_else(|| {
+                    emacs_modeline(line)?
+                        .iter()
+                        .find_map(|&(key, value)| key.eq_ignore_ascii_case("mode").then_some(value))
+                })
+        })
+        .and_then(find_syntax)
+}
+
+// The lines at the start and the end of a file that may contain modelines
+fn modelines<'a>(lines: &'a [&'a str]) -> impl Iterator<Item = &'a str> {
+    let head = lines.iter().take(MODELINES);
+    let tail = lines
+        .iter()
+        .skip(lines.len().saturating_sub(MODELINES).max(MODELINES));
+    head.chain(tail).copied()
+}
+
+/*
+ * Vim modelines look like 'vim: set ft=rust:' or 'vi: ts=4 filetype=c'.
+ * The marker has to be at the start of the line or follow whitespace.
+ */
+fn vim_modeline(line: &str) -> Option<Vec<&str>> {
+    for marker in ["vim:", "vi:", "ex:"] {
+        for (idx, _) in line.match_indices(marker) {
+            let at_word = idx == 0 || line[..idx].ends_with(char::is_whitespace);
+            if !at_word {
+                continue;
+            }
+            let options = &line[idx + marker.len()..];
+            return Some(
+                options
+                    .split(|c: char| c == ':' || c.is_whitespace())
+                    .filter(|option| !option.is_empty())
+                    .collect(),
+            );
+        }
+    }
+    None
+}
+
+/*
+ * Emacs modelines look like '-*- mode: python; tab-width: 4 -*-' or just
+ * '-*- python -*-', which is the same as a mode variable.
+ */
+fn emacs_modeline(line: &str) -> Option<Vec<(&str, &str)>> {
+    let start = line.find("-*-")? + 3;
+    let len = line[start..].find("-*-")?;
+    let vars = line[start..start + len].trim();
+    if !vars.contains(':') {
+        return Some(vec![("mode", vars)]);
+    }
+    Some(
+        vars.split(';')
+            .filter_map(|var| {
+                let (key, value) = var.split_once(':')?;
+                Some((key.trim(), value.trim()))
+            })
+            .collect(),
+    )
+}
+
+// Match a file name against a glob pattern supporting '*' and '?'
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last '*' and the name position it was tried at
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((sp, sn)) = star {
+            // Let the last '*' swallow one more character
+            p = sp + 1;
+            n = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -1,73 +1,132 @@
 use std::fmt::Display;
 use std::fs;
+use std::io;
 use std::path;
+use std::path::Path;
+use std::path::PathBuf;
 
+use crate::browser::*;
+use crate::cmdline::*;
+use crate::detect::*;
 use crate::events::*;
+use crate::finder::*;
+use crate::goto::*;
+use crate::grep::*;
+use crate::macros::*;
+use crate::prompt::*;
+use crate::quickfix::*;
 use crate::screen::*;
+use crate::shell::*;
 use crate::syntax::*;
+use crate::vi::*;
 
 pub struct Editor {
     screen: Screen,
     file: Option<String>,
+    macros: Macros,
+    recording: Option<String>, // register of the macro being recorded
+    browse: Option<String>,    // directory to list when the editor starts
+    quickfix: QuickFix,        // errors of the last build or matches of the last grep
 }
 
+// How often a macro is played
+enum Repeat {
+    Times(usize),
+    ToEnd,
+    UntilFailure,
+}
+
+// Most times a macro is played to the end of the file or until it fails, counts are not limited
+const MACRO_RUNS_LIMIT: usize = 10000;
+
 impl Editor {
     pub fn new() -> crossterm::Result<Self> {
-        Self::create(&[], None, None)
+        Self::create(&[], None, None, detect_indentation(&[], None))
     }
 
     pub fn open(file: &str) -> crossterm::Result<Self> {
-        let lines: Vec<String> = if path::Path::new(file).exists() {
-            let data = Self::read_file(file);
-            data.split('\n').map(|s: &str| s.to_string()).collect()
-        } else {
-            Vec::new()
+        if Path::new(file).is_dir() {
+            let mut editor = Self::new()?;
+            editor.browse = Some(file.to_string());
+            return Ok(editor);
+        }
+        let lines = match Self::read_lines(file) {
+            Ok(lines) => lines,
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                std::process::exit(1);
+            }
         };
-        let syntax = Self::file_syntax(file);
-        Self::create(&lines, Some(file.to_string()), syntax)
+        let syntax = Self::file_syntax(file, &lines);
+        let indentation = Self::file_indentation(&lines, syntax);
+        Self::create(&lines, Some(file.to_string()), syntax, indentation)
     }
 
     /*
-     * Look up the syntax highlight database for the file extension and
-     * return a reference to the syntax object for the file type.
+     * Look up the syntax highlight database for the file name and contents
+     * and return a reference to the syntax object for the file type.
      */
-    fn file_syntax(filename: &str) -> Option<&'static Syntax> {
-        match filename.split('.').collect::<Vec<&str>>().last() {
-            None => return None,
-            Some(ext_file) => {
-                for syntax in HLDB.iter() {
-                    for ext_db in syntax.filematch.iter() {
-                        if ext_db == ext_file {
-                            return Some(syntax);
-                        }
-                    }
-                }
-            }
-        }
-        None
+    fn file_syntax(filename: &str, lines: &[String]) -> Option<&'static Syntax> {
+        let lines: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+        detect_syntax(filename, &lines)
     }
 
-    fn read_file(file: &str) -> String {
-        match fs::read_to_string(file) {
-            Ok(data) => data,
-            Err(e) => {
-                eprintln!("{}: {}", file, e);
-                std::process::exit(1);
-            }
+    fn file_indentation(lines: &[String], syntax: Option<&Syntax>) -> Indentation {
+        let lines: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+        detect_indentation(&lines, syntax)
+    }
+
+    // Use the syntax for the buffer, along with the indentation that goes with it
+    fn set_syntax(&mut self, syntax: Option<&'static Syntax>) {
+        let lines = self.lines();
+        self.screen.set_syntax(syntax);
+        self.screen
+            .set_indentation(Self::file_indentation(&lines, syntax));
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.screen
+            .rows_to_string()
+            .split('\n')
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    // The lines of a file, none for a new file
+    fn read_lines(file: &str) -> io::Result<Vec<String>> {
+        if !path::Path::new(file).exists() {
+            return Ok(Vec::new());
         }
+        let data = fs::read_to_string(file)?;
+        Ok(data.split('\n').map(|s: &str| s.to_string()).collect())
     }
 
     pub fn create(
         lines: &[String],
         file: Option<String>,
         syntax: Option<&'static Syntax>,
+        indentation: Indentation,
     ) -> crossterm::Result<Self> {
+        let mut screen = Screen::new(lines, file.clone(), syntax, indentation)?;
+        let mut macros = Macros::new();
+        let errors = macros.load_user();
+        if !errors.is_empty() {
+            screen.set_status(&errors.join("; "));
+        }
         Ok(Self {
-            screen: Screen::new(lines, file.clone(), syntax)?,
+            screen,
             file,
+            macros,
+            recording: None,
+            browse: None,
+            quickfix: QuickFix::new(),
         })
     }
 
+    pub fn goto(&mut self, goto: Goto) {
+        self.screen.goto(goto);
+    }
+
     pub fn die<T: Display>(&mut self, message: &str, err: T) {
         let _ = self.screen.release();
         eprintln!("{}: {}", message, err);
@@ -76,6 +135,9 @@ impl Editor {
 
     pub fn run(&mut self) -> crossterm::Result<()> {
         self.screen.open()?;
+        if let Some(dir) = self.browse.take() {
+            self.browse(&dir)?;
+        }
 
         loop {
             self.screen.refresh()?;
@@ -94,50 +156,874 @@ impl Editor {
         let result = self.screen.read();
 
         match result {
-            Ok(event) => match event {
-                EditorEvent::Key(ch) => {
-                    self.screen.insert_char(ch);
-                }
-                EditorEvent::Cursor(direction) => self.screen.move_cursor(direction),
-                EditorEvent::Control(ctrl) => match ctrl {
-                    ControlEvent::Quit => {
-                        let quit_times = self.screen.dec_quit_times();
-                        if self.screen.is_dirty() && quit_times > 0 {
-                            let msg = format!("WARNING: File has unsaved changes. Press Ctrl-Q {} more time(s) to quit", quit_times);
-                            self.screen.set_status(&msg);
-                            return Ok(false);
-                        } else {
-                            return Ok(true);
-                        }
+            Ok(event) => {
+                if self.handle(event)? {
+                    return Ok(true);
+                }
+            }
+            Err(e) => {
+                self.die("Failed to read event", e);
+            }
+        }
+        Ok(false)
+    }
+
+    // Act on an event, return true to quit
+    fn handle(&mut self, event: EditorEvent) -> crossterm::Result<bool> {
+        // A match shown by grep stays highlighted until the next key
+        self.screen.clear_match();
+        match event {
+            EditorEvent::Key(ch) => {
+                self.screen.clear_selection();
+                self.screen.insert_char(ch);
+            }
+            EditorEvent::Cursor(direction) => {
+                self.screen.clear_selection();
+                self.screen.move_cursor(direction);
+            }
+            EditorEvent::Select(direction) => self.screen.select(direction),
+            EditorEvent::Vi(ViCommand::Command) => {
+                if self.command_line()? {
+                    return Ok(true);
+                }
+            }
+            EditorEvent::Vi(command) => self.screen.vi_command(command),
+            EditorEvent::Control(ctrl) => match ctrl {
+                ControlEvent::Quit => {
+                    let quit_times = self.screen.dec_quit_times();
+                    if self.screen.is_dirty() && quit_times > 0 {
+                        let msg = format!("WARNING: File has unsaved changes. Quit {} more time(s) to discard them", quit_times);
+                        self.screen.set_status(&msg);
+                        return Ok(false);
+                    } else {
+                        return Ok(true);
                     }
-                    ControlEvent::Save => self.save()?,
-                    ControlEvent::Escape => {
-                        self.screen.set_status("");
+                }
+                ControlEvent::Save => self.save()?,
+                ControlEvent::Escape => {
+                    self.screen.clear_selection();
+                    self.screen.set_status("");
+                }
+                ControlEvent::Find => {
+                    self.screen.find()?;
+                }
+                ControlEvent::Line => self.screen.toggle_line()?,
+                ControlEvent::Theme => self.screen.next_theme(),
+                ControlEvent::Syntax => self.select_syntax()?,
+                ControlEvent::Dedent => self.screen.indent_line(true),
+                ControlEvent::IndentBlock => self.screen.indent_block(false),
+                ControlEvent::DedentBlock => self.screen.indent_block(true),
+                ControlEvent::MatchBracket => self.screen.jump_to_match(),
+                ControlEvent::AutoPairs => self.screen.toggle_auto_pairs(),
+                ControlEvent::Undo => self.screen.undo(),
+                ControlEvent::Redo => self.screen.redo(),
+                ControlEvent::ToggleComment => self.screen.toggle_comment(),
+                ControlEvent::Goto => self.screen.goto_prompt()?,
+                ControlEvent::DuplicateLines => self.screen.duplicate_lines(),
+                ControlEvent::MoveLinesUp => self.screen.move_lines(true),
+                ControlEvent::MoveLinesDown => self.screen.move_lines(false),
+                ControlEvent::JoinLines => self.screen.join_lines(),
+                ControlEvent::DeleteLines => self.screen.delete_lines(),
+                ControlEvent::Help => self.screen.show_help()?,
+                ControlEvent::Recenter => self.screen.recenter(),
+                ControlEvent::SetMark => self.screen.set_mark(),
+                ControlEvent::KillLine => self.screen.kill_line(),
+                ControlEvent::KillRegion => self.screen.kill_region(),
+                ControlEvent::CopyRegion => self.screen.copy_region(),
+                ControlEvent::Yank => self.screen.yank(),
+                ControlEvent::YankPop => self.screen.yank_pop(),
+                ControlEvent::Paste => self.screen.paste(),
+                ControlEvent::RecordMacro => self.record_macro()?,
+                ControlEvent::PlayMacro => {
+                    if self.play_macro()? {
+                        return Ok(true);
                     }
-                    ControlEvent::Find => {
-                        self.screen.find()?;
+                }
+                ControlEvent::SaveMacro => self.save_macro()?,
+                ControlEvent::CommandLine => {
+                    if self.command_line()? {
+                        return Ok(true);
                     }
-                    ControlEvent::Line => self.screen.toggle_line()?,
-                },
+                }
+                ControlEvent::FindFile => self.find_file()?,
+                ControlEvent::Browse => {
+                    let dir = self
+                        .file
+                        .as_deref()
+                        .and_then(|file| Path::new(file).parent())
+                        .filter(|dir| !dir.as_os_str().is_empty())
+                        .map_or(".".to_string(), |dir| dir.display().to_string());
+                    self.browse(&dir)?;
+                }
+                ControlEvent::Grep => self.grep(None)?,
+                ControlEvent::Filter => self.filter(None, None)?,
+                ControlEvent::Build => self.build(None)?,
+                ControlEvent::QuickFixList => self.quickfix_list(None)?,
+                ControlEvent::NextError => self.next_error(true)?,
+                ControlEvent::PreviousError => self.next_error(false)?,
+            },
+        }
+        self.screen.set_last_event(event);
+        if self.screen.mode() == Some(Mode::Normal) {
+            self.screen.keep_cursor_on_char();
+        }
+        self.screen.reset_quit_times();
+        Ok(false)
+    }
+
+    fn macro_name(name: &str) -> String {
+        match name.trim() {
+            "" => DEFAULT_MACRO.to_string(),
+            name => name.to_string(),
+        }
+    }
+
+    // Start recording a macro into a named register, or stop recording it
+    fn record_macro(&mut self) -> crossterm::Result<()> {
+        if let Some(name) = self.recording.take() {
+            let events = self.screen.stop_recording().unwrap_or_default();
+            self.screen.set_status(&format!(
+                "Recorded macro '{}' ({} events)",
+                name,
+                events.len()
+            ));
+            self.macros.set(&name, events);
+        } else if let Some(name) =
+            self.screen
+                .show_prompt("Record macro into register (name)", PromptKind::Macro, None)?
+        {
+            let name = Self::macro_name(&name);
+            self.screen.start_recording();
+            self.screen
+                .set_status(&format!("Recording macro '{}', record again to stop", name));
+            self.recording = Some(name);
+        }
+        Ok(())
+    }
+
+    /*
+     * Prompt for a macro and play it: 'a' once, 'a 10' ten times, 'a eof'
+     * until the cursor reaches the end of the file and 'a fail' until it
+     * fails or changes nothing. Playing always stops at the first failure,
+     * like a search without a match or a move past the end of the file.
+     * Return true if the macro quits the editor.
+     */
+    fn play_macro(&mut self) -> crossterm::Result<bool> {
+        if self.recording.is_some() {
+            self.screen
+                .set_status("Can't play a macro while recording one");
+            return Ok(false);
+        }
+        let Some(spec) = self.screen.show_prompt(
+            "Play macro (name [count | eof | fail])",
+            PromptKind::Macro,
+            None,
+        )?
+        else {
+            return Ok(false);
+        };
+        // The name can be left out, for the default register
+        let words: Vec<&str> = spec.split_whitespace().collect();
+        let is_repeat = |w: &str| w == "eof" || w == "fail" || w.parse::<usize>().is_ok();
+        let (name, repeat) = match words[..] {
+            [] => ("", None),
+            [word] if is_repeat(word) => ("", Some(word)),
+            [name] => (name, None),
+            [name, repeat] => (name, Some(repeat)),
+            _ => {
+                self.screen
+                    .set_status(&format!("Invalid macro '{}'", spec.trim()));
+                return Ok(false);
+            }
+        };
+        let name = Self::macro_name(name);
+        let repeat = match repeat {
+            None => Repeat::Times(1),
+            Some("eof") => Repeat::ToEnd,
+            Some("fail") => Repeat::UntilFailure,
+            Some(n) => match n.parse() {
+                Ok(n) => Repeat::Times(n),
+                Err(_) => {
+                    self.screen
+                        .set_status(&format!("Invalid repeat count '{}'", n));
+                    return Ok(false);
+                }
             },
+        };
+        let events = match self.macros.get(&name) {
+            Some(events) if !events.is_empty() => events.clone(),
+            _ => {
+                self.screen.set_status(&format!("No macro '{}'", name));
+                return Ok(false);
+            }
+        };
+
+        self.screen.take_failed();
+        let mut runs = 0;
+        let mut failed = false;
+        let mut limited = false;
+        while !failed {
+            let (start, edits) = (self.screen.position(), self.screen.edits());
+            self.screen.play(&events);
+            while self.screen.is_playing() {
+                if self.event()? {
+                    return Ok(true);
+                }
+                if self.screen.take_failed() {
+                    failed = true;
+                    self.screen.stop_playing();
+                }
+            }
+            if failed {
+                break;
+            }
+            runs += 1;
+            let pos = self.screen.position();
+            let done = match repeat {
+                Repeat::Times(n) => runs >= n,
+                Repeat::ToEnd => pos.y as usize + 1 >= self.screen.rows() || pos.y <= start.y,
+                Repeat::UntilFailure => {
+                    (pos.x, pos.y, self.screen.edits()) == (start.x, start.y, edits)
+                }
+            };
+            if done {
+                break;
+            }
+            if !matches!(repeat, Repeat::Times(_)) && runs >= MACRO_RUNS_LIMIT {
+                limited = true;
+                break;
+            }
+        }
+        let stopped = if failed {
+            ", stopped by a failure".to_string()
+        } else if limited {
+            format!(", stopped at the limit of {} runs", MACRO_RUNS_LIMIT)
+        } else {
+            String::new()
+        };
+        self.screen.set_status(&format!(
+            "Played macro '{}' {} time(s){}",
+            name, runs, stopped
+        ));
+        Ok(false)
+    }
+
+    // Save a macro to the macros file, to use it in later sessions
+    fn save_macro(&mut self) -> crossterm::Result<()> {
+        if let Some(name) = self
+            .screen
+            .show_prompt("Save macro (name)", PromptKind::Macro, None)?
+        {
+            let name = Self::macro_name(&name);
+            match self.macros.save_user(&name) {
+                Ok(path) => self
+                    .screen
+                    .set_status(&format!("Saved macro '{}' to {}", name, path)),
+                Err(e) => self.screen.set_status(&e),
+            }
+        }
+        Ok(())
+    }
+
+    /*
+     * Prompt for an ex-style command and run it, return true to quit. The
+     * commands of the keymap can be run by their names as well.
+     */
+    fn command_line(&mut self) -> crossterm::Result<bool> {
+        let Some(line) = self.screen.show_prompt(":", PromptKind::Command, None)? else {
+            return Ok(false);
+        };
+        let current = self.screen.position().y as usize;
+        let command = match parse_command(&line, current, self.screen.lines()) {
+            Ok(Some(command)) => command,
+            Ok(None) => return Ok(false),
             Err(e) => {
-                self.die("Failed to read event", e);
+                self.screen.set_status(&e);
+                return Ok(false);
+            }
+        };
+        match command {
+            ExCommand::Write(None) => self.save()?,
+            ExCommand::Write(Some(file)) => self.write_copy(&file),
+            ExCommand::WriteQuit => {
+                self.save()?;
+                return Ok(!self.screen.is_dirty());
+            }
+            ExCommand::Quit(false) if self.screen.is_dirty() => self
+                .screen
+                .set_status("No write since last change (add ! to override)"),
+            ExCommand::Quit(_) => return Ok(true),
+            ExCommand::Edit(Some(file), _) if Path::new(&file).is_dir() => self.browse(&file)?,
+            ExCommand::Edit(file, force) => match file.or_else(|| self.file.clone()) {
+                Some(file) => self.edit(&file, force),
+                None => self.screen.set_status("No file name"),
+            },
+            ExCommand::Set(options) if options.is_empty() => {
+                let options = self.screen.options();
+                self.screen.set_status(&options);
+            }
+            ExCommand::Set(options) => {
+                for option in options {
+                    if let Err(e) = self.screen.set_option(&option) {
+                        self.screen.set_status(&e);
+                        break;
+                    }
+                }
             }
+            ExCommand::Syntax(name) => self.use_syntax(&name),
+            ExCommand::Substitute {
+                first,
+                last,
+                pattern,
+                replacement,
+                global,
+            } => {
+                self.screen
+                    .substitute(first, last, &pattern, &replacement, global);
+            }
+            ExCommand::Sort {
+                first,
+                last,
+                reverse,
+            } => self.screen.sort_lines(first, last, reverse),
+            ExCommand::Filter {
+                first,
+                last,
+                command,
+            } => self.filter(Some((first, last)), Some(command))?,
+            ExCommand::Grep(query) if query.is_empty() => self.grep(None)?,
+            ExCommand::Grep(query) => self.grep(Some(query))?,
+            ExCommand::Make(command) => self.build(command)?,
+            ExCommand::Goto(line) => self.screen.goto(Goto {
+                line: line + 1,
+                col: None,
+            }),
+            ExCommand::Run(event) => return self.handle(event),
         }
-        self.screen.reset_quit_times();
         Ok(false)
     }
 
+    // Pick a file under the working directory by fuzzy search and open it
+    fn find_file(&mut self) -> crossterm::Result<()> {
+        let files = project_files(".");
+        if files.is_empty() {
+            self.screen.set_status("No files found");
+        } else if let Some((at, _)) =
+            self.screen
+                .pick("Open file", &files, Some(rank_paths), &[], 0)?
+        {
+            // The changes of the file are kept if it is picked again
+            let file = &files[at];
+            if !self.is_current_file(file) && self.save_before_opening(file)? {
+                self.edit(file, false);
+            }
+        }
+        Ok(())
+    }
+
+    /*
+     * Search the files under the working directory for a query, prompted
+     * for if not given, and open the match picked from the list of them.
+     */
+    fn grep(&mut self, query: Option<String>) -> crossterm::Result<()> {
+        let query = match query {
+            Some(query) => query,
+            None => {
+                match self
+                    .screen
+                    .show_prompt("Grep (text or /regex/)", PromptKind::Grep, None)?
+                {
+                    Some(query) if !query.is_empty() => query,
+                    _ => {
+                        self.screen.set_status("Cancelled grep");
+                        return Ok(());
+                    }
+                }
+            }
+        };
+        let pattern = match grep_pattern(&query) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                self.screen.set_status(&e);
+                return Ok(());
+            }
+        };
+        let matches = grep(".", &pattern);
+        if matches.is_empty() {
+            self.screen
+                .set_status(&format!("No matches for '{}'", query));
+            return Ok(());
+        }
+        let entries: Vec<String> = matches.iter().map(GrepMatch::entry).collect();
+        let title = format!("{} matches for '{}'", matches.len(), query);
+        self.quickfix.set(
+            matches
+                .into_iter()
+                .map(|m| Location {
+                    message: m.text.trim().to_string(),
+                    file: m.file,
+                    line: m.line,
+                    col: m.col,
+                    len: m.len,
+                })
+                .collect(),
+        );
+        self.quickfix_list(Some((&title, &entries)))
+    }
+
+    /*
+     * Filter lines, the selected ones if not given or else the whole file,
+     * through a shell command prompted for if not given. The lines go to
+     * the standard input of the command and are replaced with its output
+     * as one change, unless it fails.
+     */
+    fn filter(
+        &mut self,
+        lines: Option<(usize, usize)>,
+        command: Option<String>,
+    ) -> crossterm::Result<()> {
+        let lines = lines.or_else(|| self.screen.selected_lines());
+        let command = match command {
+            Some(command) => command,
+            None => {
+                let prompt = match lines {
+                    Some((first, last)) => {
+                        format!("Filter lines {}-{} through", first + 1, last + 1)
+                    }
+                    None => "Filter the file through".to_string(),
+                };
+                match self.screen.show_prompt(&prompt, PromptKind::Filter, None)? {
+                    Some(command) if !command.trim().is_empty() => command,
+                    _ => {
+                        self.screen.set_status("Cancelled filter");
+                        return Ok(());
+                    }
+                }
+            }
+        };
+        let (input, count) = match lines {
+            Some((first, last)) => (self.screen.rows_text(first, last), last + 1 - first),
+            None => (self.screen.rows_to_string(), self.screen.lines()),
+        };
+        let output = match run_shell(&command, Some(&input)) {
+            Ok(output) => output,
+            Err(e) => {
+                self.screen.fail(&e);
+                return Ok(());
+            }
+        };
+        let errors = match output.errors() {
+            errors if errors.is_empty() => String::new(),
+            errors => format!(": {}", errors),
+        };
+        if !output.success() {
+            self.screen.fail(&format!(
+                "'{}' failed ({}){}",
+                command,
+                output.exit(),
+                errors
+            ));
+            return Ok(());
+        }
+        // The rows of the whole file end with the empty one after its last newline
+        let (first, last, rows): (usize, usize, Vec<String>) = match lines {
+            Some((first, last)) => (
+                first,
+                last,
+                output.stdout.lines().map(String::from).collect(),
+            ),
+            None => (
+                0,
+                input.split('\n').count() - 1,
+                output.stdout.split('\n').map(String::from).collect(),
+            ),
+        };
+        self.screen.replace_rows(first, last, &rows);
+        self.screen.set_status(&format!(
+            "Filtered {} lines through '{}' ({}){}",
+            count,
+            command,
+            output.exit(),
+            errors
+        ));
+        Ok(())
+    }
+
+    /*
+     * Run the build command, the configured one if not given, and list
+     * the errors found in its output.
+     */
+    fn build(&mut self, command: Option<String>) -> crossterm::Result<()> {
+        let command = command.unwrap_or_else(|| self.screen.config().build_command.clone());
+        self.screen.set_status(&format!("Running '{}'...", command));
+        self.screen.refresh()?;
+        let (output, success) = match run_build(&command) {
+            Ok(result) => result,
+            Err(e) => {
+                self.screen.set_status(&e);
+                return Ok(());
+            }
+        };
+        self.quickfix.set(parse_locations(&output));
+        let errors = self.quickfix.locations().len();
+        if errors > 0 {
+            let title = format!("'{}': {} errors", command, errors);
+            let entries: Vec<String> = self
+                .quickfix
+                .locations()
+                .iter()
+                .map(Location::entry)
+                .collect();
+            return self.quickfix_list(Some((&title, &entries)));
+        }
+        if success {
+            self.screen.set_status(&format!("'{}' succeeded", command));
+        } else {
+            let last = output.lines().rev().find(|line| !line.trim().is_empty());
+            self.screen.set_status(&format!(
+                "'{}' failed: {}",
+                command,
+                last.unwrap_or("no output")
+            ));
+        }
+        Ok(())
+    }
+
+    /*
+     * List the locations of the last build or grep, with a title and
+     * entries of their own or as 'file:line:col: message', and go to the
+     * one picked.
+     */
+    fn quickfix_list(&mut self, list: Option<(&str, &[String])>) -> crossterm::Result<()> {
+        if self.quickfix.locations().is_empty() {
+            self.screen.set_status("No errors");
+            return Ok(());
+        }
+        let (title, entries) = match list {
+            Some((title, entries)) => (title.to_string(), entries.to_vec()),
+            None => (
+                format!("{} errors", self.quickfix.locations().len()),
+                self.quickfix
+                    .locations()
+                    .iter()
+                    .map(Location::entry)
+                    .collect(),
+            ),
+        };
+        let selected = self.quickfix.current().unwrap_or(0);
+        if let Some((at, _)) = self.screen.pick(&title, &entries, None, &[], selected)? {
+            self.show_location(at)?;
+        }
+        Ok(())
+    }
+
+    // Go to the next or previous location of the last build or grep
+    fn next_error(&mut self, forwards: bool) -> crossterm::Result<()> {
+        let at = if forwards {
+            self.quickfix.next()
+        } else {
+            self.quickfix.previous()
+        };
+        match at {
+            Some(at) => self.show_location(at)?,
+            None if self.quickfix.locations().is_empty() => self.screen.fail("No errors"),
+            None => self.screen.fail("No more errors"),
+        }
+        Ok(())
+    }
+
+    /*
+     * Open a location of the quickfix list and show its message. It becomes
+     * the current one only if it could be opened.
+     */
+    fn show_location(&mut self, at: usize) -> crossterm::Result<()> {
+        let location = &self.quickfix.locations()[at];
+        let (file, line, col, len) = (
+            location.file.clone(),
+            location.line,
+            location.col,
+            location.len,
+        );
+        let message = format!(
+            "({}/{}) {}",
+            at + 1,
+            self.quickfix.locations().len(),
+            location.message
+        );
+        if self.open_location(&file, line, col, len)? {
+            self.quickfix.select(at);
+            self.screen.set_status(&message);
+        }
+        Ok(())
+    }
+
+    /*
+     * Open a file, unless it is the one being edited, and show 'len'
+     * characters at 'line' and 'col' in it. Unsaved changes are saved
+     * first if the user agrees. Return false if the file was not opened.
+     */
+    fn open_location(
+        &mut self,
+        file: &str,
+        line: usize,
+        col: usize,
+        len: usize,
+    ) -> crossterm::Result<bool> {
+        if !self.is_current_file(file) {
+            if !self.save_before_opening(file)? {
+                return Ok(false);
+            }
+            self.edit(file, false);
+            if !self.is_current_file(file) {
+                return Ok(false);
+            }
+        }
+        self.screen.show_match(line, col, len);
+        Ok(true)
+    }
+
+    // Offer to save the changes of the buffer before another file is opened, return if it can be
+    fn save_before_opening(&mut self, file: &str) -> crossterm::Result<bool> {
+        if !self.screen.is_dirty() {
+            return Ok(true);
+        }
+        let name = self.file.clone().unwrap_or_else(|| "the file".to_string());
+        if !self
+            .screen
+            .confirm(&format!("Save {} before opening {}?", name, file))?
+        {
+            self.screen.fail("No write since last change");
+            return Ok(false);
+        }
+        // The message of a failed save says why
+        self.save()?;
+        Ok(!self.screen.is_dirty())
+    }
+
+    fn is_current_file(&self, file: &str) -> bool {
+        match &self.file {
+            Some(current) => match (fs::canonicalize(current), fs::canonicalize(file)) {
+                (Ok(current), Ok(file)) => current == file,
+                _ => current == file,
+            },
+            None => false,
+        }
+    }
+
+    /*
+     * List the files of a directory to open one of them. Enter opens a
+     * file or goes into a directory, '..' or Backspace goes up. Files and
+     * directories can be created, renamed and deleted from the list.
+     */
+    fn browse(&mut self, dir: &str) -> crossterm::Result<()> {
+        let mut dir = match fs::canonicalize(dir) {
+            Ok(dir) => dir,
+            Err(e) => {
+                self.screen.set_status(&format!("{}: {}", dir, e));
+                return Ok(());
+            }
+        };
+        let actions = [
+            EditorEvent::Cursor(CursorKey::Backspace),
+            EditorEvent::Cursor(CursorKey::Delete),
+            EditorEvent::Key('n'),
+            EditorEvent::Key('+'),
+            EditorEvent::Key('r'),
+        ];
+        let mut selected = 0;
+        // Result of the last change, shown instead of the keys
+        let mut message: Option<String> = None;
+
+        loop {
+            let entries = match list_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    self.screen.set_status(&format!("{}: {}", dir.display(), e));
+                    return Ok(());
+                }
+            };
+            let width = entries.iter().map(|e| e.name.len() + 1).max().unwrap_or(0);
+            let lines: Vec<String> = entries.iter().map(|e| e.line(width.min(40))).collect();
+            let keys = "Enter opens, Backspace goes up, n new file, + new directory, r renames, Del deletes";
+            let title = format!(
+                "{} - {}",
+                Self::display_path(&dir),
+                message.take().as_deref().unwrap_or(keys)
+            );
+            let Some((at, event)) = self.screen.pick(&title, &lines, None, &actions, selected)?
+            else {
+                return Ok(());
+            };
+            selected = at;
+            let entry = &entries[at];
+            let path = dir.join(&entry.name);
+            match event {
+                EditorEvent::Cursor(CursorKey::Backspace) => {
+                    if let Some(parent) = dir.parent() {
+                        dir = parent.to_path_buf();
+                        selected = 0;
+                    }
+                }
+                EditorEvent::Cursor(CursorKey::Enter) if entry.name == PARENT => {
+                    if let Some(parent) = dir.parent() {
+                        dir = parent.to_path_buf();
+                        selected = 0;
+                    }
+                }
+                EditorEvent::Cursor(CursorKey::Enter) if entry.is_dir => {
+                    dir = path;
+                    selected = 0;
+                }
+                EditorEvent::Cursor(CursorKey::Enter) => {
+                    self.edit(&Self::display_path(&path), false);
+                    return Ok(());
+                }
+                EditorEvent::Key('n') | EditorEvent::Key('+') => {
+                    let dir_wanted = event == EditorEvent::Key('+');
+                    let prompt = if dir_wanted {
+                        "New directory"
+                    } else {
+                        "New file"
+                    };
+                    if let Some(name) = self.screen.show_prompt(prompt, PromptKind::SaveAs, None)? {
+                        let path = dir.join(name.trim());
+                        let result = if dir_wanted {
+                            fs::create_dir(&path)
+                        } else {
+                            fs::File::create_new(&path).map(|_| ())
+                        };
+                        message = Some(Self::browse_result(result, &path, "Created"));
+                        selected = Self::entry_index(&path);
+                    }
+                }
+                EditorEvent::Key('r') if entry.name != PARENT => {
+                    let name = entry.name.clone();
+                    let prompt = format!("Rename '{}' to", name);
+                    if let Some(new_name) =
+                        self.screen
+                            .show_prompt_with(&prompt, PromptKind::SaveAs, None, &name)?
+                    {
+                        let new_path = dir.join(new_name.trim());
+                        if !new_path.exists()
+                            || self
+                                .screen
+                                .confirm(&format!("'{}' exists, replace it?", new_name.trim()))?
+                        {
+                            let result = fs::rename(&path, &new_path);
+                            message = Some(Self::browse_result(result, &new_path, "Renamed to"));
+                            selected = Self::entry_index(&new_path);
+                        }
+                    }
+                }
+                EditorEvent::Cursor(CursorKey::Delete) if entry.name != PARENT => {
+                    let question = if entry.is_dir {
+                        format!("Delete directory '{}' and all its files?", entry.name)
+                    } else {
+                        format!("Delete '{}'?", entry.name)
+                    };
+                    if self.screen.confirm(&question)? {
+                        let result = if entry.is_dir {
+                            fs::remove_dir_all(&path)
+                        } else {
+                            fs::remove_file(&path)
+                        };
+                        message = Some(Self::browse_result(result, &path, "Deleted"));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // The message for the result of a file operation of the directory browser
+    fn browse_result(result: io::Result<()>, path: &Path, done: &str) -> String {
+        match result {
+            Ok(()) => format!("{} {}", done, Self::display_path(path)),
+            Err(e) => format!("{}: {}", Self::display_path(path), e),
+        }
+    }
+
+    // Where a file is listed in its directory, to select it after a change
+    fn entry_index(path: &Path) -> usize {
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+            return 0;
+        };
+        list_dir(dir).map_or(0, |entries| {
+            entries
+                .iter()
+                .position(|entry| entry.name == name.to_string_lossy())
+                .unwrap_or(0)
+        })
+    }
+
+    // A path relative to the working directory if it is under it
+    fn display_path(path: &Path) -> String {
+        let relative = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| path.strip_prefix(cwd).ok().map(PathBuf::from));
+        match relative {
+            Some(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+            Some(relative) => relative.display().to_string(),
+            None => path.display().to_string(),
+        }
+    }
+
+    // Replace the buffer with a file, refused if there are unsaved changes unless forced
+    fn edit(&mut self, file: &str, force: bool) {
+        if self.screen.is_dirty() && !force {
+            self.screen
+                .set_status("No write since last change (add ! to override)");
+            return;
+        }
+        let lines = match Self::read_lines(file) {
+            Ok(lines) => lines,
+            Err(e) => {
+                self.screen.set_status(&format!("{}: {}", file, e));
+                return;
+            }
+        };
+        let syntax = Self::file_syntax(file, &lines);
+        let indentation = Self::file_indentation(&lines, syntax);
+        self.screen
+            .load(&lines, Some(file.to_string()), syntax, indentation);
+        self.file = Some(file.to_string());
+        self.screen
+            .set_status(&format!("\"{}\" {} lines", file, lines.len()));
+    }
+
+    // Write the buffer to another file, the buffer keeps its own name if it has one
+    fn write_copy(&mut self, file: &str) {
+        if self.file.is_none() {
+            if self.save_as(file) {
+                let syntax = Self::file_syntax(file, &self.lines());
+                self.set_syntax(syntax);
+                self.file = Some(file.to_string());
+            }
+        } else {
+            self.write_file(file);
+        }
+    }
+
     pub fn save(&mut self) -> crossterm::Result<()> {
         let filename = if let Some(filename) = self.file.clone() {
             Some(filename)
         } else {
-            self.screen.show_prompt("Save as", None)?
+            self.screen
+                .show_prompt("Save as", PromptKind::SaveAs, None)?
         };
         if let Some(filename) = filename {
+            // Keep the syntax of a named file, which may have been set manually
+            if self.file.is_none() {
+                let syntax = Self::file_syntax(&filename, &self.lines());
+                self.set_syntax(syntax);
+            }
+            let formatted = self.format_buffer();
             if self.save_as(&filename) {
-                self.screen.set_syntax(Self::file_syntax(&filename));
                 self.file = Some(filename);
+                if let Err(e) = formatted {
+                    self.screen
+                        .set_status(&format!("Saved without formatting: {}", e));
+                }
             }
         } else {
             self.screen.set_status("Cancelled save");
@@ -145,12 +1031,84 @@ impl Editor {
         Ok(())
     }
 
+    /*
+     * Format the buffer with the formatter of its syntax before it is
+     * saved. The buffer is replaced only if the formatter succeeds, else
+     * the error is returned. A formatter that is not installed is the same
+     * as none.
+     */
+    fn format_buffer(&mut self) -> Result<(), String> {
+        let config = self.screen.config();
+        let command = match self.screen.syntax() {
+            Some(syntax) if config.format_on_save => config.formatter(syntax),
+            _ => None,
+        };
+        let Some(command) = command else {
+            return Ok(());
+        };
+        let input = self.screen.rows_to_string();
+        let output = run_shell(&command, Some(&input))?;
+        // The shell exits with 127 when it cannot find the command
+        if output.code == Some(127) {
+            return Ok(());
+        }
+        if !output.success() {
+            return Err(format!(
+                "'{}' failed ({}): {}",
+                command,
+                output.exit(),
+                output.errors()
+            ));
+        }
+        // A formatter that prints nothing likely changed the file itself
+        if output.stdout.is_empty() && !input.is_empty() {
+            return Err(format!("'{}' printed nothing", command));
+        }
+        if output.stdout != input {
+            let rows: Vec<String> = output.stdout.split('\n').map(String::from).collect();
+            self.screen.reformat(&rows);
+        }
+        Ok(())
+    }
+
+    // Prompt for a syntax name and use it to highlight the file
+    pub fn select_syntax(&mut self) -> crossterm::Result<()> {
+        if let Some(name) =
+            self.screen
+                .show_prompt("Syntax (name or 'none')", PromptKind::Syntax, None)?
+        {
+            self.use_syntax(&name);
+        }
+        Ok(())
+    }
+
+    fn use_syntax(&mut self, name: &str) {
+        if name.trim() == "none" {
+            self.set_syntax(None);
+            self.screen.set_status("Syntax highlighting disabled");
+        } else if let Some(syntax) = find_syntax(name) {
+            self.set_syntax(Some(syntax));
+            self.screen
+                .set_status(&format!("Syntax set to {}", syntax.filetype));
+        } else {
+            self.screen
+                .set_status(&format!("Unknown syntax '{}'", name.trim()));
+        }
+    }
+
     pub fn save_as(&mut self, filename: &str) -> bool {
+        let written = self.write_file(filename);
+        if written {
+            self.screen.set_dirty(false);
+        }
+        written
+    }
+
+    fn write_file(&mut self, filename: &str) -> bool {
         let buf = self.screen.rows_to_string();
         match fs::write(filename, &buf) {
             Ok(_) => {
-                let file_len = buf.as_bytes().len();
-                self.screen.set_dirty(false);
+                let file_len = buf.len();
                 self.screen
                     .set_status(&format!("{} bytes written to {}", file_len, filename));
                 true
@@ -0,0 +1,100 @@
+use crate::events::*;
+
+// Number of killed texts kept for yanking
+const KILL_RING_SIZE: usize = 60;
+
+/*
+ * The emacs editing mode on top of its keymap. Setting the mark starts a
+ * region, which the movement keys extend until it is used or cancelled.
+ */
+pub struct Emacs {
+    mark: bool, // the region is active
+}
+
+impl Emacs {
+    pub fn new() -> Self {
+        Self { mark: false }
+    }
+
+    pub fn keymap_event(&mut self, event: EditorEvent) -> EditorEvent {
+        match event {
+            EditorEvent::Control(ControlEvent::SetMark) => self.mark = true,
+            EditorEvent::Cursor(key) if self.mark && Self::is_motion(key) => {
+                return EditorEvent::Select(key);
+            }
+            EditorEvent::Key(_)
+            | EditorEvent::Cursor(_)
+            | EditorEvent::Control(
+                ControlEvent::Escape
+                | ControlEvent::KillLine
+                | ControlEvent::KillRegion
+                | ControlEvent::CopyRegion
+                | ControlEvent::Yank
+                | ControlEvent::YankPop
+                | ControlEvent::Undo
+                | ControlEvent::Redo,
+            ) => self.mark = false,
+            _ => {}
+        }
+        event
+    }
+
+    fn is_motion(key: CursorKey) -> bool {
+        !matches!(
+            key,
+            CursorKey::Delete
+                | CursorKey::Backspace
+                | CursorKey::Enter
+                | CursorKey::DeleteWordLeft
+                | CursorKey::DeleteWordRight
+        )
+    }
+}
+
+/*
+ * Killed text, the newest last. Yanking takes the newest text, yanking
+ * again right after that replaces it with the ones before in turn.
+ */
+pub struct KillRing {
+    entries: Vec<String>,
+    yank: usize, // entry to yank next
+}
+
+impl KillRing {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            yank: 0,
+        }
+    }
+
+    // Add killed text, kills that follow each other are added to one entry
+    pub fn kill(&mut self, text: &str, append: bool) {
+        match self.entries.last_mut() {
+            Some(last) if append => last.push_str(text),
+            _ => {
+                if self.entries.len() == KILL_RING_SIZE {
+                    self.entries.remove(0);
+                }
+                self.entries.push(text.to_string());
+            }
+        }
+        self.yank = self.entries.len() - 1;
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.entries.get(self.yank).map(String::as_str)
+    }
+
+    // Move on to the entry killed before the current one, the oldest wraps around to the newest
+    pub fn rotate(&mut self) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.yank = match self.yank {
+            0 => self.entries.len() - 1,
+            n => n - 1,
+        };
+        self.current()
+    }
+}
@@ -11,20 +11,112 @@ pub enum CursorKey {
     Delete,
     Backspace,
     Enter,
+    WordLeft,
+    WordRight,
+    FileStart,
+    FileEnd,
+    DeleteWordLeft,
+    DeleteWordRight,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ControlEvent {
     Quit,
     Save,
     Escape,
     Find,
     Line,
+    Theme,
+    Syntax,
+    Dedent,
+    IndentBlock,
+    DedentBlock,
+    MatchBracket,
+    AutoPairs,
+    Undo,
+    Redo,
+    ToggleComment,
+    Goto,
+    DuplicateLines,
+    MoveLinesUp,
+    MoveLinesDown,
+    JoinLines,
+    DeleteLines,
+    Help,
+    Recenter,
+    SetMark,
+    KillLine,
+    KillRegion,
+    CopyRegion,
+    Yank,
+    YankPop,
+    RecordMacro,
+    PlayMacro,
+    SaveMacro,
+    CommandLine,
+    FindFile,
+    Browse,
+    Grep,
+    Build,
+    QuickFixList,
+    NextError,
+    PreviousError,
+    Filter,
+    Paste, // text pasted in the terminal, read from the input
+}
+
+// Where the cursor moves to with a vi motion
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordStart,
+    WordEnd,
+    WordBack,
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    Lines,                   // the line of the cursor and the ones below, as in 'dd'
+    GotoLine(Option<usize>), // 'gg' and 'G', None is the last line
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+// Where text is inserted when switching to insert mode
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InsertAt {
+    Cursor,
+    After,
+    LineStart,
+    LineEnd,
+    LineBelow,
+    LineAbove,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ViCommand {
+    Move(Motion, usize),
+    Operate(Operator, Motion, usize),
+    Insert(InsertAt),
+    NormalMode, // back from insert mode
+    Visual,
+    VisualOperate(Operator),
+    Paste(bool, usize), // before the cursor or after it, how many times
+    Command,            // ':' commands
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum EditorEvent {
     Key(char),
     Control(ControlEvent),
     Cursor(CursorKey),
+    Select(CursorKey),
+    Vi(ViCommand),
 }
@@ -0,0 +1,119 @@
+use ignore::WalkBuilder;
+
+// Most files listed, for huge trees
+const MAX_FILES: usize = 50000;
+
+// Scores of a matched character and the bonuses for where it is
+const SCORE_MATCH: i64 = 16;
+const BONUS_PATH: i64 = 10; // start of a path component
+const BONUS_BOUNDARY: i64 = 8; // after '_', '-', '.' or a space
+const BONUS_CAMEL: i64 = 6; // upper case after lower case
+const BONUS_CONSECUTIVE: i64 = 8;
+const BONUS_BASENAME: i64 = 2;
+
+/*
+ * The files under 'root', as paths relative to it, sorted. Files ignored
+ * by .gitignore and .ignore files and hidden ones are left out.
+ */
+pub fn project_files(root: &str) -> Vec<String> {
+    let mut files: Vec<String> = WalkBuilder::new(root)
+        .require_git(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let path = entry.path().strip_prefix(root).ok()?;
+            Some(path.to_string_lossy().to_string())
+        })
+        .take(MAX_FILES)
+        .collect();
+    files.sort();
+    files
+}
+
+/*
+ * Score how well 'query' matches 'candidate', which has to contain its
+ * characters in order, ignoring case. Matches at the start of words and
+ * path components and runs of consecutive characters score higher, gaps
+ * between the matched characters lower. The best placement is found by
+ * dynamic programming over the query and candidate characters.
+ */
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|ch| ch.to_lowercase().next().unwrap_or(*ch))
+        .collect();
+    let basename = chars
+        .iter()
+        .rposition(|&ch| ch == '/')
+        .map_or(0, |at| at + 1);
+    let bonus: Vec<i64> = (0..chars.len())
+        .map(|i| {
+            let mut bonus = SCORE_MATCH;
+            match i.checked_sub(1).map(|prev| chars[prev]) {
+                None | Some('/') => bonus += BONUS_PATH,
+                Some('_' | '-' | '.' | ' ') => bonus += BONUS_BOUNDARY,
+                Some(prev) if prev.is_lowercase() && chars[i].is_uppercase() => {
+                    bonus += BONUS_CAMEL
+                }
+                _ => {}
+            }
+            if i >= basename {
+                bonus += BONUS_BASENAME;
+            }
+            bonus
+        })
+        .collect();
+
+    // Best score of the query so far with its last character matched at each index
+    let mut scores: Vec<Option<i64>> = lower
+        .iter()
+        .zip(&bonus)
+        .map(|(&ch, &bonus)| (ch == query[0]).then_some(bonus))
+        .collect();
+    for &qc in &query[1..] {
+        let mut next = vec![None; chars.len()];
+        // Best of scores[k] + k for k < i - 1, a gap costs a point per character
+        let mut best_gap: Option<i64> = None;
+        for i in 1..chars.len() {
+            if i >= 2 {
+                if let Some(score) = scores[i - 2] {
+                    best_gap = best_gap.max(Some(score + i as i64 - 2));
+                }
+            }
+            if lower[i] != qc {
+                continue;
+            }
+            let consecutive = scores[i - 1].map(|score| score + BONUS_CONSECUTIVE);
+            let gap = best_gap.map(|best| best - i as i64 + 1);
+            next[i] = consecutive.max(gap).map(|score| score + bonus[i]);
+        }
+        scores = next;
+    }
+    scores.into_iter().flatten().max()
+}
+
+// Indices of the paths that match 'query', the best matches first, shorter paths before longer ones
+pub fn rank_paths(query: &str, paths: &[String]) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = paths
+        .iter()
+        .enumerate()
+        .filter_map(|(i, path)| fuzzy_score(query, path).map(|score| (score, i)))
+        .collect();
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then(paths[*a].len().cmp(&paths[*b].len()))
+            .then(a.cmp(b))
+    });
+    scored.into_iter().map(|(_, i)| i).collect()
+}
@@ -0,0 +1,68 @@
+/*
+ * The line of 'new' that line 'y' of 'old' became, for text that was
+ * reformatted. Lines before the first changed line and after the last one
+ * only move by the lines added or removed in between. A changed line goes
+ * to the nearest line with the same text apart from whitespace, or else
+ * to the same place in proportion among the changed lines.
+ */
+pub fn map_line(old: &[String], new: &[String], y: usize) -> usize {
+    if new.is_empty() {
+        return 0;
+    }
+    let last = new.len() - 1;
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    if y < prefix {
+        return y.min(last);
+    }
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(old.len().min(new.len()) - prefix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    if y >= old_end {
+        return (y - old_end + new_end).min(last);
+    }
+    let guess = prefix + (y - prefix) * (new_end - prefix) / (old_end - prefix);
+    let text = squeeze(&old[y]);
+    if !text.is_empty() {
+        let same = (prefix..new_end)
+            .filter(|&i| squeeze(&new[i]) == text)
+            .min_by_key(|&i| i.abs_diff(guess));
+        if let Some(same) = same {
+            return same;
+        }
+    }
+    guess.min(last)
+}
+
+/*
+ * The column of 'new_row' that column 'x' of 'old_row' became, for rows
+ * that only differ in whitespace: the one before the same non-whitespace
+ * character.
+ */
+pub fn map_col(old_row: &str, new_row: &str, x: usize) -> usize {
+    let before = old_row
+        .chars()
+        .take(x)
+        .filter(|ch| !ch.is_whitespace())
+        .count();
+    let mut seen = 0;
+    for (i, ch) in new_row.chars().enumerate() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        if seen == before {
+            return i;
+        }
+        seen += 1;
+    }
+    new_row.chars().count()
+}
+
+// Text without its whitespace, to compare rows that were reformatted
+pub fn squeeze(row: &str) -> String {
+    row.chars().filter(|ch| !ch.is_whitespace()).collect()
+}
@@ -0,0 +1,69 @@
+use std::path;
+
+// A position to jump to. Lines and columns are counted from 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Goto {
+    pub line: usize,
+    pub col: Option<usize>,
+}
+
+/*
+ * Parse a position like '42', '42:7', '+10', '-3' or '50%'. Relative lines
+ * are counted from 'current' and percentages of 'total', both in lines.
+ */
+pub fn parse_goto(spec: &str, current: usize, total: usize) -> Option<Goto> {
+    let (line, col) = match spec.trim().split_once(':') {
+        Some((line, col)) => (line.trim(), Some(col.trim().parse::<usize>().ok()?)),
+        None => (spec.trim(), None),
+    };
+    let line = if let Some(n) = line.strip_prefix('+') {
+        current.saturating_add(n.parse().ok()?)
+    } else if let Some(n) = line.strip_prefix('-') {
+        current.saturating_sub(n.parse().ok()?)
+    } else if let Some(percent) = line.strip_suffix('%') {
+        let percent: usize = percent.trim().parse().ok()?;
+        (total * percent.min(100) + 50) / 100
+    } else {
+        line.parse().ok()?
+    };
+    Some(Goto {
+        line: line.clamp(1, total.max(1)),
+        col,
+    })
+}
+
+/*
+ * Split a command line argument like 'src/main.rs:10:5' into the file and
+ * the position. An existing file whose name ends like a position wins.
+ */
+pub fn split_file_location(arg: &str) -> (&str, Option<Goto>) {
+    if path::Path::new(arg).exists() {
+        return (arg, None);
+    }
+    let numbers: Vec<&str> = arg
+        .rsplitn(3, ':')
+        .take(2)
+        .take_while(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+        .collect();
+    let goto = match numbers[..] {
+        [col, line] => Some((line, Some(col))),
+        [line] => Some((line, None)),
+        _ => None,
+    };
+    match goto {
+        Some((line, col)) => {
+            // An argument that is only a position is a file name
+            let position_len = line.len() + 1 + col.map_or(0, |c| c.len() + 1);
+            let file_len = match arg.len().checked_sub(position_len) {
+                Some(file_len) if file_len > 0 => file_len,
+                _ => return (arg, None),
+            };
+            let goto = Goto {
+                line: line.parse().unwrap_or(1).max(1),
+                col: col.and_then(|c| c.parse().ok()),
+            };
+            (&arg[..file_len], Some(goto))
+        }
+        None => (arg, None),
+    }
+}
@@ -0,0 +1,75 @@
+use regex::Regex;
+
+use std::fs;
+use std::path::Path;
+
+use crate::finder::*;
+
+// Most matches listed, for patterns that match almost everything
+const MAX_MATCHES: usize = 10000;
+
+// Bytes at the start of a file looked at to tell if it is binary
+const BINARY_CHECK_LEN: usize = 8000;
+
+// A line of a file that matches, the line and column counted from 0, the column in characters
+pub struct GrepMatch {
+    pub file: String,
+    pub line: usize,
+    pub col: usize,
+    pub len: usize, // characters matched
+    pub text: String,
+}
+
+impl GrepMatch {
+    // The match as 'file:line: text', the line counted from 1
+    pub fn entry(&self) -> String {
+        format!("{}:{}: {}", self.file, self.line + 1, self.text.trim())
+    }
+}
+
+/*
+ * The pattern of a grep query. A query between slashes like '/fn \w+/' is
+ * a regular expression, anything else is searched for literally.
+ */
+pub fn grep_pattern(query: &str) -> Result<Regex, String> {
+    let pattern = match query.strip_prefix('/').and_then(|q| q.strip_suffix('/')) {
+        Some(pattern) if !pattern.is_empty() => pattern.to_string(),
+        _ => regex::escape(query),
+    };
+    Regex::new(&pattern).map_err(|e| format!("Invalid pattern: {}", e))
+}
+
+/*
+ * The first match of 'pattern' on each line of the files under 'root',
+ * the files of the file finder, in order. Binary files and files that
+ * are not UTF-8 are skipped.
+ */
+pub fn grep(root: &str, pattern: &Regex) -> Vec<GrepMatch> {
+    let mut matches = Vec::new();
+    for file in project_files(root) {
+        let Ok(data) = fs::read(Path::new(root).join(&file)) else {
+            continue;
+        };
+        if data[..data.len().min(BINARY_CHECK_LEN)].contains(&0) {
+            continue;
+        }
+        let Ok(data) = String::from_utf8(data) else {
+            continue;
+        };
+        for (line, text) in data.lines().enumerate() {
+            if let Some(m) = pattern.find(text) {
+                matches.push(GrepMatch {
+                    file: file.clone(),
+                    line,
+                    col: text[..m.start()].chars().count(),
+                    len: m.as_str().chars().count(),
+                    text: text.to_string(),
+                });
+                if matches.len() == MAX_MATCHES {
+                    return matches;
+                }
+            }
+        }
+    }
+    matches
+}
@@ -0,0 +1,65 @@
+// Number of lines a history keeps
+const HISTORY_SIZE: usize = 100;
+
+/*
+ * Lines entered at a prompt, the newest last. Browsing starts after the
+ * newest one, at the line being typed, which is kept to come back to.
+ */
+pub struct History {
+    entries: Vec<String>,
+    index: usize,  // entry shown while browsing, entries.len() for the typed line
+    typed: String, // line typed before browsing started
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: 0,
+            typed: String::new(),
+        }
+    }
+
+    // Start browsing from the line being typed
+    pub fn reset(&mut self) {
+        self.index = self.entries.len();
+        self.typed.clear();
+    }
+
+    // Add an entered line, moving an equal older one to the end
+    pub fn add(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            return;
+        }
+        self.entries.retain(|entry| entry != line);
+        if self.entries.len() == HISTORY_SIZE {
+            self.entries.remove(0);
+        }
+        self.entries.push(line.to_string());
+        self.reset();
+    }
+
+    // The entry before the one shown, 'line' being what the prompt shows now
+    pub fn older(&mut self, line: &str) -> Option<&str> {
+        if self.index == 0 {
+            return None;
+        }
+        if self.index == self.entries.len() {
+            self.typed = line.to_string();
+        }
+        self.index -= 1;
+        self.entries.get(self.index).map(String::as_str)
+    }
+
+    // The entry after the one shown, the typed line after the newest one
+    pub fn newer(&mut self) -> Option<&str> {
+        if self.index >= self.entries.len() {
+            return None;
+        }
+        self.index += 1;
+        match self.entries.get(self.index) {
+            Some(entry) => Some(entry),
+            None => Some(&self.typed),
+        }
+    }
+}
@@ -4,71 +4,209 @@ use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyModifiers;
 
+use std::collections::VecDeque;
+
+use crate::config::*;
+use crate::emacs::*;
 use crate::events::*;
+use crate::keymap::*;
+use crate::vi::*;
 
-pub struct Input;
+pub struct Input {
+    keymap: Keymap,
+    pending: Vec<Key>, // keys of a sequence typed so far
+    vi: Option<Vi>,
+    emacs: Option<Emacs>,
+    queued: VecDeque<EditorEvent>, // events of a command or macro that are still to be read
+    recording: Option<Vec<EditorEvent>>, // events read while recording a macro
+    pasted: String,                // text of the last paste in the terminal
+}
 
 impl Input {
-    pub fn new() -> Self {
-        Self {}
-    }
-
-    // Check if ch is a valid file name character
-    pub fn is_valid_file_char(ch: char) -> bool {
-        (' '..='~').contains(&ch)
-    }
-
-    // Decode key, return None if it can be ignored
-    pub fn key_event(&self, key: KeyEvent) -> Option<EditorEvent> {
-        match key {
-            // Control keys
-            KeyEvent {
-                code: KeyCode::Char(ch),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => match ch {
-                'q' => Some(EditorEvent::Control(ControlEvent::Quit)),
-                'h' => Some(EditorEvent::Cursor(CursorKey::Backspace)),
-                's' => Some(EditorEvent::Control(ControlEvent::Save)),
-                'f' => Some(EditorEvent::Control(ControlEvent::Find)),
-                'l' => Some(EditorEvent::Control(ControlEvent::Line)),
-                _ => None,
-            },
-            // Cursor and character keys
-            KeyEvent {
-                code,
-                modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
-                ..
-            } => match code {
-                KeyCode::Up => Some(EditorEvent::Cursor(CursorKey::Up)),
-                KeyCode::Left => Some(EditorEvent::Cursor(CursorKey::Left)),
-                KeyCode::Down => Some(EditorEvent::Cursor(CursorKey::Down)),
-                KeyCode::Right => Some(EditorEvent::Cursor(CursorKey::Right)),
-                KeyCode::PageUp => Some(EditorEvent::Cursor(CursorKey::PageUp)),
-                KeyCode::PageDown => Some(EditorEvent::Cursor(CursorKey::PageDown)),
-                KeyCode::Home => Some(EditorEvent::Cursor(CursorKey::Home)),
-                KeyCode::End => Some(EditorEvent::Cursor(CursorKey::End)),
-                KeyCode::Delete => Some(EditorEvent::Cursor(CursorKey::Delete)),
-                KeyCode::Backspace => Some(EditorEvent::Cursor(CursorKey::Backspace)),
-                KeyCode::Enter => Some(EditorEvent::Cursor(CursorKey::Enter)),
-                KeyCode::Tab => Some(EditorEvent::Key('\t')),
-                KeyCode::Char(ch) => Some(EditorEvent::Key(ch)),
-                KeyCode::Esc => Some(EditorEvent::Control(ControlEvent::Escape)),
-                _ => None,
-            },
-            KeyEvent { .. } => None,
+    pub fn new(editing_mode: EditingMode) -> Self {
+        Self {
+            keymap: Keymap::new(editing_mode),
+            pending: Vec::new(),
+            vi: (editing_mode == EditingMode::Vi).then(Vi::new),
+            emacs: (editing_mode == EditingMode::Emacs).then(Emacs::new),
+            queued: VecDeque::new(),
+            recording: None,
+            pasted: String::new(),
         }
     }
 
-    pub fn read(&self) -> crossterm::Result<EditorEvent> {
+    // The vi mode, None if vi editing is not enabled
+    pub fn mode(&self) -> Option<Mode> {
+        self.vi.as_ref().map(Vi::mode)
+    }
+
+    // Load the keymap file of the user, return the errors in it
+    pub fn load_keymap(&mut self) -> Vec<String> {
+        self.keymap.load_user()
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    // Check if ch can be typed at a prompt
+    pub fn is_prompt_char(ch: char) -> bool {
+        !ch.is_control()
+    }
+
+    /*
+     * Decode a key for the editor. With vi editing, characters typed outside
+     * of insert mode make up vi commands and Esc switches back to normal
+     * mode, all other keys go through the keymap. With emacs editing the
+     * movement keys extend the region while the mark is set.
+     */
+    pub fn key_event(&mut self, event: KeyEvent) -> Option<EditorEvent> {
+        let key = Key::from_event(event);
+        if let Some(vi) = &mut self.vi {
+            if key.modifiers == KeyModifiers::NONE {
+                if key.code == KeyCode::Esc {
+                    self.pending.clear();
+                    return Some(vi.escape());
+                }
+                if let (KeyCode::Char(ch), false) = (key.code, vi.mode() == Mode::Insert) {
+                    self.pending.clear();
+                    let mut events = vi.normal_key(ch).into_iter();
+                    let event = events.next();
+                    self.queued.extend(events);
+                    return event;
+                }
+            }
+        }
+        let event = self.keymap_event(key)?;
+        match (&mut self.vi, &mut self.emacs) {
+            (Some(vi), _) => vi.keymap_event(event),
+            (_, Some(emacs)) => Some(emacs.keymap_event(event)),
+            _ => Some(event),
+        }
+    }
+
+    /*
+     * Decode a key through the keymap. Return None if it can be ignored or
+     * starts a sequence of keys, like Ctrl-K in 'Ctrl-K Ctrl-C'. Character
+     * keys that are not bound insert the character.
+     */
+    fn keymap_event(&mut self, key: Key) -> Option<EditorEvent> {
+        self.pending.push(key);
+        match self.keymap.lookup(&self.pending) {
+            Binding::Event(event) => {
+                self.pending.clear();
+                Some(event)
+            }
+            Binding::Prefix => None,
+            Binding::Unbound => {
+                let in_sequence = self.pending.len() > 1;
+                self.pending.clear();
+                match key.code {
+                    KeyCode::Char(ch) if key.modifiers.is_empty() && !in_sequence => {
+                        Some(EditorEvent::Key(ch))
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    // Stop recording a macro, return the events recorded
+    pub fn stop_recording(&mut self) -> Option<Vec<EditorEvent>> {
+        self.recording.take()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    // Queue the events of a macro to be read as if they were typed
+    pub fn play(&mut self, events: &[EditorEvent]) {
+        self.queued.extend(events);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.queued.is_empty()
+    }
+
+    pub fn stop_playing(&mut self) {
+        self.queued.clear();
+    }
+
+    // The text of the last paste, with its line breaks as '\n'
+    pub fn pasted(&self) -> &str {
+        &self.pasted
+    }
+
+    fn record(&mut self, event: EditorEvent) {
+        // A macro keeps what was pasted as the keys that type it
+        if let (Some(events), EditorEvent::Control(ControlEvent::Paste)) =
+            (&mut self.recording, event)
+        {
+            events.extend(self.pasted.chars().map(|ch| match ch {
+                '\n' => EditorEvent::Cursor(CursorKey::Enter),
+                ch => EditorEvent::Key(ch),
+            }));
+            return;
+        }
+        let macro_command = matches!(
+            event,
+            EditorEvent::Control(
+                ControlEvent::RecordMacro | ControlEvent::PlayMacro | ControlEvent::SaveMacro
+            )
+        );
+        if let (Some(events), false) = (&mut self.recording, macro_command) {
+            events.push(event);
+        }
+    }
+
+    // Read the next event for the editor
+    pub fn read(&mut self) -> crossterm::Result<EditorEvent> {
+        let event = match self.queued.pop_front() {
+            Some(event) => {
+                if let Some(vi) = &mut self.vi {
+                    vi.replay(event);
+                }
+                event
+            }
+            None => self.read_events(true)?,
+        };
+        self.record(event);
+        Ok(event)
+    }
+
+    // Read an event through the keymap only, for prompts
+    pub fn read_plain(&mut self) -> crossterm::Result<EditorEvent> {
+        let event = match self.queued.pop_front() {
+            Some(event) => event,
+            None => self.read_events(false)?,
+        };
+        self.record(event);
+        Ok(event)
+    }
+
+    fn read_events(&mut self, modal: bool) -> crossterm::Result<EditorEvent> {
         loop {
             match event::read() {
                 Ok(event) => match event {
                     Event::Key(key) => {
-                        if let Some(key) = self.key_event(key) {
-                            return Ok(key);
+                        let event = if modal {
+                            self.key_event(key)
+                        } else {
+                            self.keymap_event(Key::from_event(key))
+                        };
+                        if let Some(event) = event {
+                            return Ok(event);
                         }
                     }
+                    Event::Paste(text) => {
+                        self.pasted = text.replace("\r\n", "\n").replace('\r', "\n");
+                        return Ok(EditorEvent::Control(ControlEvent::Paste));
+                    }
                     _ => {
                         println!("other event\r");
                     }
@@ -0,0 +1,282 @@
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+
+use std::fmt;
+use std::fs;
+
+use crate::commands::*;
+use crate::config::*;
+use crate::events::*;
+
+const KEYMAP_FILE: &str = "keymap";
+
+// A key with its modifiers, as it appears in key bindings
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /*
+     * Bring a key read from the terminal into the form used by bindings.
+     * Shift is part of the character for character keys and of Shift-Tab,
+     * and terminals send Ctrl-/ as Ctrl-7.
+     */
+    pub fn from_event(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        let code = match event.code {
+            KeyCode::Char('7') if modifiers == KeyModifiers::CONTROL => KeyCode::Char('/'),
+            KeyCode::Char(ch) => {
+                let ch = Self::shift_char(ch, modifiers);
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(ch)
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+        Self::new(code, modifiers)
+    }
+
+    /*
+     * Parse a key like 'Ctrl-K', 'Alt-Up', 'Shift-Tab' or 'F5'. The Emacs
+     * notation 'C-x' and 'M-f' is accepted as well.
+     */
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        loop {
+            let (prefix, modifier) = match rest.split_once('-') {
+                Some((prefix, name)) if !name.is_empty() => (prefix, name),
+                _ => break,
+            };
+            let modifier_flag = match prefix.to_lowercase().as_str() {
+                "ctrl" | "c" => KeyModifiers::CONTROL,
+                "alt" | "meta" | "m" => KeyModifiers::ALT,
+                "shift" | "s" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier in '{}'", s)),
+            };
+            modifiers.insert(modifier_flag);
+            rest = modifier;
+        }
+
+        let code = match rest.to_lowercase().as_str() {
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "backspace" => KeyCode::Backspace,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            "tab" => KeyCode::Tab,
+            name if name.len() > 1 && name.starts_with('f') => match name[1..].parse() {
+                Ok(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                _ => return Err(format!("unknown key '{}'", s)),
+            },
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    // Control and Alt letters are read in lower case, unless shifted
+                    (Some(ch), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                        KeyCode::Char(Self::shift_char(ch, modifiers))
+                    }
+                    (Some(ch), None) if modifiers.is_empty() => KeyCode::Char(ch),
+                    (Some(ch), None) => KeyCode::Char(ch.to_ascii_lowercase()),
+                    _ => return Err(format!("unknown key '{}'", s)),
+                }
+            }
+        };
+        if matches!(code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Ok(Self::new(code, modifiers))
+    }
+
+    // A letter typed with Shift, which terminals send either way, is an upper case one
+    fn shift_char(ch: char, modifiers: KeyModifiers) -> char {
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            ch.to_ascii_uppercase()
+        } else {
+            ch
+        }
+    }
+
+    // Parse a sequence of keys separated by spaces, like 'Ctrl-K Ctrl-C'
+    pub fn parse_sequence(s: &str) -> Result<Vec<Self>, String> {
+        let keys = s
+            .split_whitespace()
+            .map(Self::parse)
+            .collect::<Result<Vec<Self>, String>>()?;
+        if keys.is_empty() {
+            return Err("no keys given".to_string());
+        }
+        Ok(keys)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(ch) if !self.modifiers.is_empty() => {
+                write!(f, "{}", ch.to_ascii_uppercase())
+            }
+            KeyCode::Char(ch) => write!(f, "{}", ch),
+            KeyCode::BackTab => write!(f, "Shift-Tab"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+// Write a key sequence the way it is written in keymap files
+pub fn keys_to_string(keys: &[Key]) -> String {
+    keys.iter()
+        .map(|key| key.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Result of looking up the keys typed so far
+pub enum Binding {
+    Event(EditorEvent),
+    Prefix, // the keys start a longer sequence
+    Unbound,
+}
+
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, EditorEvent)>,
+}
+
+impl Keymap {
+    // The default bindings of the editing mode, overridden by the keymap file of the user
+    pub fn new(editing_mode: EditingMode) -> Self {
+        let mut keymap = Self {
+            bindings: Vec::new(),
+        };
+        keymap.bind_all(DEFAULT_BINDINGS);
+        if editing_mode == EditingMode::Emacs {
+            keymap.bind_all(EMACS_BINDINGS);
+        }
+        keymap
+    }
+
+    fn bind_all(&mut self, bindings: &[(&str, &str)]) {
+        for (keys, name) in bindings {
+            let Ok(keys) = Key::parse_sequence(keys) else {
+                continue;
+            };
+            if *name == "none" {
+                self.unbind(&keys);
+            } else if let Some(command) = find_command(name) {
+                self.bind(keys, command.event);
+            }
+        }
+    }
+
+    // Bind keys to an event, replacing an existing binding of the same keys
+    pub fn bind(&mut self, keys: Vec<Key>, event: EditorEvent) {
+        self.unbind(&keys);
+        self.bindings.push((keys, event));
+    }
+
+    pub fn unbind(&mut self, keys: &[Key]) {
+        self.bindings.retain(|(k, _)| k != keys);
+    }
+
+    /*
+     * Load the keymap file of the user. Each line binds a key sequence to
+     * a command, 'Ctrl-K Ctrl-C = toggle-comment', or removes a binding
+     * with 'Ctrl-Q = none'. Errors are returned, one for each bad line.
+     */
+    pub fn load_user(&mut self) -> Vec<String> {
+        let path = match config_path(KEYMAP_FILE) {
+            Some(path) if path.exists() => path,
+            _ => return Vec::new(),
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => self.load(&text),
+            Err(e) => vec![format!("{}: {}", path.display(), e)],
+        }
+    }
+
+    pub fn load(&mut self, text: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        for setting in parse_settings(text) {
+            let (lno, keys, name) = match setting {
+                Ok(setting) => setting,
+                Err(e) => {
+                    errors.push(format!("keymap {}", e));
+                    continue;
+                }
+            };
+            let keys = match Key::parse_sequence(keys) {
+                Ok(keys) => keys,
+                Err(e) => {
+                    errors.push(format!("keymap line {}: {}", lno, e));
+                    continue;
+                }
+            };
+            if name == "none" {
+                self.unbind(&keys);
+            } else if let Some(command) = find_command(name) {
+                self.bind(keys, command.event);
+            } else {
+                errors.push(format!("keymap line {}: unknown command '{}'", lno, name));
+            }
+        }
+        errors
+    }
+
+    pub fn lookup(&self, keys: &[Key]) -> Binding {
+        let mut prefix = false;
+        for (k, event) in self.bindings.iter() {
+            if k == keys {
+                return Binding::Event(*event);
+            }
+            prefix |= k.starts_with(keys);
+        }
+        if prefix {
+            Binding::Prefix
+        } else {
+            Binding::Unbound
+        }
+    }
+
+    // All the key sequences bound to an event
+    pub fn keys_for(&self, event: EditorEvent) -> Vec<&[Key]> {
+        self.bindings
+            .iter()
+            .filter(|(_, e)| *e == event)
+            .map(|(keys, _)| keys.as_slice())
+            .collect()
+    }
+}
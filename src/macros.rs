@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crate::commands::*;
+use crate::config::*;
+use crate::events::*;
+use crate::vi::*;
+
+const MACROS_FILE: &str = "macros";
+// Register used when no name is given
+pub const DEFAULT_MACRO: &str = "default";
+
+/*
+ * Recorded macros by register name. Macros can be saved to the macros file
+ * in the config directory, one per line as 'name = events'. The events
+ * are written as command names, typed text in quotes and vi commands as
+ * 'vi:' followed by their keys, like: a = line-start "// " down vi:2dw
+ */
+pub struct Macros {
+    registers: HashMap<String, Vec<EditorEvent>>,
+}
+
+impl Macros {
+    pub fn new() -> Self {
+        Self {
+            registers: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Vec<EditorEvent>> {
+        self.registers.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, events: Vec<EditorEvent>) {
+        self.registers.insert(name.to_string(), events);
+    }
+
+    // Load the macros saved in earlier sessions, return the errors in the file
+    pub fn load_user(&mut self) -> Vec<String> {
+        let path = match config_path(MACROS_FILE) {
+            Some(path) if path.exists() => path,
+            _ => return Vec::new(),
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => return vec![format!("{}: {}", path.display(), e)],
+        };
+        let mut errors = Vec::new();
+        for setting in parse_settings(&text) {
+            match setting {
+                Ok((lno, name, events)) => match Self::parse_events(events) {
+                    Ok(events) => self.set(name, events),
+                    Err(e) => errors.push(format!("macros line {}: {}", lno, e)),
+                },
+                Err(e) => errors.push(format!("macros {}", e)),
+            }
+        }
+        errors
+    }
+
+    // Write a macro to the macros file, replacing an older one of the same name
+    pub fn save_user(&self, name: &str) -> Result<String, String> {
+        let events = self
+            .get(name)
+            .ok_or_else(|| format!("No macro '{}'", name))?;
+        let path = config_path(MACROS_FILE).ok_or("No config directory")?;
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let mut lines: Vec<String> = text
+            .lines()
+            .filter(|line| {
+                line.split_once('=')
+                    .is_none_or(|(key, _)| key.trim() != name)
+            })
+            .map(String::from)
+            .collect();
+        lines.push(format!("{} = {}", name, Self::events_to_string(events)));
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        fs::write(&path, lines.join("\n") + "\n")
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(path.display().to_string())
+    }
+
+    fn events_to_string(events: &[EditorEvent]) -> String {
+        let mut words = Vec::new();
+        let mut text = String::new();
+        for event in events {
+            match event {
+                EditorEvent::Key(ch) if *ch != '\t' => {
+                    text.push(*ch);
+                    continue;
+                }
+                _ => {}
+            }
+            if !text.is_empty() {
+                words.push(format!("{:?}", text));
+                text.clear();
+            }
+            match event {
+                EditorEvent::Vi(command) => {
+                    words.push(format!("vi:{}", Vi::command_keys(*command)))
+                }
+                event => {
+                    if let Some(command) = command_for_event(*event) {
+                        words.push(command.name.to_string());
+                    }
+                }
+            }
+        }
+        if !text.is_empty() {
+            words.push(format!("{:?}", text));
+        }
+        words.join(" ")
+    }
+
+    fn parse_events(s: &str) -> Result<Vec<EditorEvent>, String> {
+        let mut events = Vec::new();
+        let mut chars = s.chars().peekable();
+        while let Some(&ch) = chars.peek() {
+            if ch.is_whitespace() {
+                chars.next();
+            } else if ch == '"' {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => events.push(EditorEvent::Key('\n')),
+                            Some('t') => events.push(EditorEvent::Key('\t')),
+                            Some('r') => events.push(EditorEvent::Key('\r')),
+                            Some('0') => events.push(EditorEvent::Key('\0')),
+                            Some('u') => {
+                                let code: String = chars
+                                    .by_ref()
+                                    .skip_while(|&c| c == '{')
+                                    .take_while(|&c| c != '}')
+                                    .collect();
+                                let ch = u32::from_str_radix(&code, 16)
+                                    .ok()
+                                    .and_then(char::from_u32)
+                                    .ok_or_else(|| format!("bad escape '\\u{{{}}}'", code))?;
+                                events.push(EditorEvent::Key(ch));
+                            }
+                            Some(ch) => events.push(EditorEvent::Key(ch)),
+                            None => return Err("unterminated text".to_string()),
+                        },
+                        Some(ch) => events.push(EditorEvent::Key(ch)),
+                        None => return Err("unterminated text".to_string()),
+                    }
+                }
+            } else {
+                let mut word = String::new();
+                while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace()) {
+                    word.push(ch);
+                }
+                let event = match word.strip_prefix("vi:") {
+                    Some(keys) => Vi::parse_command(keys).map(EditorEvent::Vi),
+                    None => find_command(&word).map(|command| command.event),
+                };
+                events.push(event.ok_or_else(|| format!("unknown command '{}'", word))?);
+            }
+        }
+        Ok(events)
+    }
+}
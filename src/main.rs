@@ -1,23 +1,51 @@
 use crate::editor::*;
+use crate::goto::*;
 
+mod bracket;
+mod browser;
+mod cmdline;
+mod commands;
+mod config;
 mod data;
+mod detect;
 mod dimensions;
 mod editor;
+mod emacs;
 mod events;
+mod finder;
+mod format;
+mod goto;
+mod grep;
 mod highlight;
+mod history;
 mod input;
+mod keymap;
+mod macros;
+mod prompt;
+mod quickfix;
 mod screen;
 mod search;
+mod shell;
 mod state;
 mod syntax;
+mod theme;
+#[cfg(feature = "treesitter")]
+mod treesitter;
+mod undo;
+mod vi;
 
 fn main() -> crossterm::Result<()> {
     let args: Vec<String> = std::env::args().collect();
     let mut editor = if args.len() < 2 {
-        Editor::new()
+        Editor::new()?
     } else {
-        Editor::open(&args[1])
-    }?;
+        let (file, goto) = split_file_location(&args[1]);
+        let mut editor = Editor::open(file)?;
+        if let Some(goto) = goto {
+            editor.goto(goto);
+        }
+        editor
+    };
 
     editor.run()
 }
@@ -0,0 +1,152 @@
+use crate::cmdline::*;
+use crate::data::*;
+use crate::events::*;
+use crate::input::*;
+
+// Kinds of prompts, each keeps its own history
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PromptKind {
+    Search,
+    SaveAs,
+    Goto,
+    Command,
+    Syntax,
+    Macro,
+    Grep,
+    Filter,
+}
+
+impl PromptKind {
+    // The lines that complete the line typed, for the prompts that complete
+    pub fn complete(self, line: &str) -> Vec<String> {
+        match self {
+            PromptKind::Command => complete_command(line),
+            PromptKind::SaveAs => complete_path(line),
+            PromptKind::Syntax => complete_syntax(line),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/*
+ * The line typed at a prompt with a cursor in it, both counted in
+ * characters. Words are the same as in the rows of the editor.
+ */
+pub struct LineEditor {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self {
+            chars: Vec::new(),
+            cursor: 0,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    // Replace the line, the cursor goes to its end
+    pub fn set(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    pub fn insert(&mut self, ch: char) {
+        self.chars.insert(self.cursor, ch);
+        self.cursor += 1;
+    }
+
+    // Insert text like pasted text, which ends at the first line break
+    pub fn insert_str(&mut self, text: &str) {
+        let line = text.lines().next().unwrap_or("");
+        for ch in line.chars().filter(|ch| !ch.is_control()) {
+            self.insert(ch);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.chars.len();
+    }
+
+    pub fn word_left(&mut self) {
+        self.cursor = EditRow::word_left_of(&self.chars, self.cursor);
+    }
+
+    pub fn word_right(&mut self) {
+        self.cursor = EditRow::word_right_of(&self.chars, self.cursor);
+    }
+
+    pub fn delete_word_left(&mut self) {
+        let start = EditRow::word_left_of(&self.chars, self.cursor);
+        self.chars.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    pub fn delete_word_right(&mut self) {
+        let end = EditRow::word_right_of(&self.chars, self.cursor);
+        self.chars.drain(self.cursor..end);
+    }
+
+    pub fn delete_to_end(&mut self) {
+        self.chars.truncate(self.cursor);
+    }
+
+    // Edit the line for a key typed, other keys are ignored
+    pub fn edit(&mut self, event: EditorEvent) {
+        match event {
+            EditorEvent::Key(ch) if Input::is_prompt_char(ch) => self.insert(ch),
+            EditorEvent::Cursor(CursorKey::Backspace) => self.backspace(),
+            EditorEvent::Cursor(CursorKey::Delete) => self.delete(),
+            EditorEvent::Cursor(CursorKey::DeleteWordLeft) => self.delete_word_left(),
+            EditorEvent::Cursor(CursorKey::DeleteWordRight) => self.delete_word_right(),
+            EditorEvent::Control(ControlEvent::KillLine) => self.delete_to_end(),
+            EditorEvent::Cursor(CursorKey::Left) => self.left(),
+            EditorEvent::Cursor(CursorKey::Right) => self.right(),
+            EditorEvent::Cursor(CursorKey::Home) => self.home(),
+            EditorEvent::Cursor(CursorKey::End) => self.end(),
+            EditorEvent::Cursor(CursorKey::WordLeft) => self.word_left(),
+            EditorEvent::Cursor(CursorKey::WordRight) => self.word_right(),
+            _ => {}
+        }
+    }
+
+    /*
+     * The part of the line that fits in 'width' columns, scrolled so that
+     * the cursor is in it, and the column of the cursor in that part.
+     */
+    pub fn view(&self, width: usize) -> (String, usize) {
+        let width = width.max(1);
+        let start = (self.cursor + 1).saturating_sub(width);
+        let end = (start + width).min(self.chars.len());
+        (self.chars[start..end].iter().collect(), self.cursor - start)
+    }
+}
@@ -0,0 +1,134 @@
+use regex::Regex;
+
+use std::path::Path;
+
+use crate::shell::*;
+
+// A place in a file with a message, the line and column counted from 0, the column in characters
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub col: usize,
+    pub len: usize, // characters to highlight
+    pub message: String,
+}
+
+impl Location {
+    // The location as 'file:line:col: message', counted from 1
+    pub fn entry(&self) -> String {
+        format!(
+            "{}:{}:{}: {}",
+            self.file,
+            self.line + 1,
+            self.col + 1,
+            self.message
+        )
+    }
+}
+
+/*
+ * The locations of a build or a search to go through one after the
+ * other, starting before the first one. A location becomes the current
+ * one only once it was gone to.
+ */
+pub struct QuickFix {
+    locations: Vec<Location>,
+    current: Option<usize>,
+}
+
+impl QuickFix {
+    pub fn new() -> Self {
+        Self {
+            locations: Vec::new(),
+            current: None,
+        }
+    }
+
+    pub fn set(&mut self, locations: Vec<Location>) {
+        self.locations = locations;
+        self.current = None;
+    }
+
+    pub fn locations(&self) -> &[Location] {
+        &self.locations
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    // Make a location the current one, once it was gone to
+    pub fn select(&mut self, at: usize) {
+        if at < self.locations.len() {
+            self.current = Some(at);
+        }
+    }
+
+    // Index of the location after the current one
+    pub fn next(&self) -> Option<usize> {
+        let at = self.current.map_or(0, |at| at + 1);
+        (at < self.locations.len()).then_some(at)
+    }
+
+    // Index of the location before the current one
+    pub fn previous(&self) -> Option<usize> {
+        self.current?.checked_sub(1)
+    }
+}
+
+// Run a build command and return its output, standard error after standard output, and whether it succeeded
+pub fn run_build(command: &str) -> Result<(String, bool), String> {
+    let output = run_shell(command, None)?;
+    let success = output.success();
+    Ok((output.stdout + &output.stderr, success))
+}
+
+/*
+ * The locations in the output of a build: lines like 'file:line:col:
+ * message' of gcc and most compilers, and the '--> file:line:col' lines of
+ * rustc, whose message is on the 'error' or 'warning' line before. Only
+ * the locations of existing files are kept.
+ */
+pub fn parse_locations(output: &str) -> Vec<Location> {
+    let plain = Regex::new(r"^([^\s:][^:]*):(\d+):(\d+):\s*(.*)$").unwrap();
+    let arrow = Regex::new(r"^\s*--> (.+):(\d+):(\d+)$").unwrap();
+    let header = Regex::new(r"^((error|warning)(\[\w+\])?: .*)$").unwrap();
+    let mut locations = Vec::new();
+    let mut message = String::new();
+    for line in output.lines() {
+        let (file, number, col, text) = if let Some(caps) = header.captures(line) {
+            message = caps[1].to_string();
+            continue;
+        } else if let Some(caps) = arrow.captures(line) {
+            (
+                caps[1].to_string(),
+                caps[2].parse::<usize>(),
+                caps[3].parse::<usize>(),
+                message.clone(),
+            )
+        } else if let Some(caps) = plain.captures(line) {
+            (
+                caps[1].to_string(),
+                caps[2].parse::<usize>(),
+                caps[3].parse::<usize>(),
+                caps[4].to_string(),
+            )
+        } else {
+            continue;
+        };
+        let (Ok(number), Ok(col)) = (number, col) else {
+            continue;
+        };
+        if !Path::new(&file).is_file() {
+            continue;
+        }
+        locations.push(Location {
+            file,
+            line: number.saturating_sub(1),
+            col: col.saturating_sub(1),
+            len: 0,
+            message: text,
+        });
+    }
+    locations
+}
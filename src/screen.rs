@@ -1,26 +1,50 @@
 use crossterm::cursor;
+use crossterm::event;
 use crossterm::style;
 use crossterm::terminal;
+use crossterm::ExecutableCommand;
 use crossterm::QueueableCommand;
 
+use regex::Regex;
+
+use std::cmp;
+use std::collections::HashMap;
 use std::io;
 use std::io::Write;
 use std::time;
 use std::time::Duration;
 
+use crate::bracket::*;
+use crate::commands::*;
+use crate::config::*;
 use crate::data::*;
+use crate::detect::*;
 use crate::dimensions::*;
+use crate::emacs::*;
 use crate::events::*;
+use crate::format::*;
+use crate::goto::*;
+use crate::highlight::*;
+use crate::history::*;
 use crate::input::*;
+use crate::keymap::*;
+use crate::prompt::*;
 use crate::search::*;
 use crate::state::*;
 use crate::syntax::*;
+use crate::theme::*;
+#[cfg(feature = "treesitter")]
+use crate::treesitter::*;
+use crate::undo::*;
+use crate::vi::*;
 
 pub struct Screen {
     input: Input,
+    config: Config,
     stdout: io::Stdout,
     window: Window,
     cursor: Position,
+    anchor: Option<Position>, // other end of the selection, the cursor being one end
     lno_width: usize,
     editrows: Vec<EditRow>,
     rowoff: usize,
@@ -31,67 +55,191 @@ pub struct Screen {
     status_msg: String,
     status_time: time::Instant,
     search_info: SearchInfo,
+    unbalanced: Option<Vec<Position>>, // brackets without a partner, worked out again after an edit
+    histories: HashMap<PromptKind, History>,
+    undo: UndoStack,
+    register: Register, // text deleted or yanked in vi mode
+    kill_ring: KillRing,
+    last_yank: Option<(Position, Position)>, // start and end of the text yanked last
+    last_event: Option<EditorEvent>,
+    failed: bool, // a command failed, which stops playing a macro
+    edits: usize, // number of changes made to the buffer
     syntax: Option<&'static Syntax>,
+    indentation: Indentation,
+    auto_pairs: bool, // close brackets and quotes as they are typed
+    theme: &'static Theme,
+    truecolor: bool,
+    #[cfg(feature = "treesitter")]
+    treesitter: Option<TreeSitter>,
 }
 
 type PromptCallback = fn(&mut Screen, &str, EditorEvent) -> bool;
+// Indices of the items that match a query, the best match first
+type RankFn = fn(&str, &[String]) -> Vec<usize>;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const QUIT_TIMES: u8 = 3;
 const LNO_WIDTH_EXTRA: usize = 1;
-const HELP_TEXT: &str = "Ctrl-Q: quit, Ctrl-S: save, Ctrl-F: find, Ctrl+L: lno";
+const QUOTES: [char; 2] = ['"', '\''];
+// Commands shown with their keys in the message bar at the start
+const HELP_COMMANDS: [&str; 5] = ["quit", "save", "find", "goto", "help"];
 
 impl Screen {
     pub fn new(
         lines: &[String],
         file: Option<String>,
         syntax: Option<&'static Syntax>,
+        indentation: Indentation,
     ) -> crossterm::Result<Self> {
         let (width, height) = crossterm::terminal::size()?;
+        let (config, mut errors) = Config::load();
         let mut screen = Self {
-            input: Input::new(),
+            input: Input::new(config.editing_mode),
+            config,
             stdout: io::stdout(),
             // One row on the bottom for status bar
             window: Window::new(width, height - 2),
             cursor: Position::new(0, 0),
+            anchor: None,
             lno_width: 0,
-            editrows: Self::make_editrows(lines),
+            editrows: Self::make_editrows(lines, indentation.tabstop),
             rowoff: 0,
             coloff: 0,
             file,
             dirty: false,
             quit_times: QUIT_TIMES,
-            status_msg: String::from(HELP_TEXT),
+            status_msg: String::new(),
             status_time: time::Instant::now(),
             search_info: SearchInfo::new(),
+            unbalanced: None,
+            histories: HashMap::new(),
+            undo: UndoStack::new(),
+            register: Register::default(),
+            kill_ring: KillRing::new(),
+            last_yank: None,
+            last_event: None,
+            failed: false,
+            edits: 0,
             syntax,
+            indentation,
+            auto_pairs: true,
+            theme: &THEMES[0],
+            truecolor: Theme::truecolor_supported(),
+            #[cfg(feature = "treesitter")]
+            treesitter: syntax.and_then(TreeSitter::new),
         };
         screen.update_syntax_all();
+        errors.extend(screen.input.load_keymap());
+        if errors.is_empty() {
+            screen.set_status(&screen.help_text());
+        } else {
+            screen.set_status(&errors.join("; "));
+        }
         Ok(screen)
     }
 
-    pub fn make_editrows(lines: &[String]) -> Vec<EditRow> {
+    pub fn make_editrows(lines: &[String], tabstop: u16) -> Vec<EditRow> {
         let editrows = lines
             .iter()
-            .map(|line| EditRow::new(line.to_string(), false))
+            .map(|line| EditRow::new(line.to_string(), false, tabstop))
             .collect::<Vec<EditRow>>();
 
         editrows
     }
 
+    // Replace the buffer with the lines of another file
+    pub fn load(
+        &mut self,
+        lines: &[String],
+        file: Option<String>,
+        syntax: Option<&'static Syntax>,
+        indentation: Indentation,
+    ) {
+        self.editrows = Self::make_editrows(lines, indentation.tabstop);
+        self.indentation = indentation;
+        self.file = file;
+        self.cursor = Position::new(0, 0);
+        self.anchor = None;
+        self.rowoff = 0;
+        self.coloff = 0;
+        self.dirty = false;
+        self.search_info = SearchInfo::new();
+        self.undo = UndoStack::new();
+        self.last_yank = None;
+        if self.lno_width != 0 {
+            self.lno_width = self.editrows.len().to_string().len() + LNO_WIDTH_EXTRA;
+        }
+        self.set_syntax(syntax);
+    }
+
+    pub fn syntax(&self) -> Option<&'static Syntax> {
+        self.syntax
+    }
+
     pub fn set_syntax(&mut self, syntax: Option<&'static Syntax>) {
         self.syntax = syntax;
+        #[cfg(feature = "treesitter")]
+        {
+            self.treesitter = syntax.and_then(TreeSitter::new);
+        }
         self.update_syntax_all();
     }
 
+    pub fn set_indentation(&mut self, indentation: Indentation) {
+        if indentation.tabstop != self.indentation.tabstop {
+            for row in self.editrows.iter_mut() {
+                row.set_tabstop(indentation.tabstop);
+            }
+            self.update_syntax_all();
+        }
+        self.indentation = indentation;
+    }
+
+    // Switch to the next built-in theme
+    pub fn next_theme(&mut self) {
+        let idx = THEMES
+            .iter()
+            .position(|t| std::ptr::eq(t, self.theme))
+            .unwrap_or(0);
+        self.theme = &THEMES[(idx + 1) % THEMES.len()];
+        self.set_status(&format!("Theme: {}", self.theme.name));
+    }
+
+    /*
+     * Reset all the attributes and apply the colors and attributes of 'style'.
+     * Colors that the style does not set are taken from the normal text.
+     */
+    fn set_style(&mut self, style: Style) -> crossterm::Result<()> {
+        let colors = style.colors(&self.theme.normal(), self.truecolor);
+        self.stdout
+            .queue(style::SetAttribute(style::Attribute::Reset))?
+            .queue(style::SetColors(colors))?;
+        for attr in style.attributes() {
+            self.stdout.queue(style::SetAttribute(attr))?;
+        }
+        Ok(())
+    }
+
     pub fn open(&mut self) -> crossterm::Result<()> {
-        terminal::enable_raw_mode()
+        terminal::enable_raw_mode()?;
+        // Pasted text comes as a whole instead of as keys, line breaks included
+        self.stdout.execute(event::EnableBracketedPaste)?;
+        Ok(())
     }
 
-    pub fn read(&self) -> crossterm::Result<EditorEvent> {
+    pub fn read(&mut self) -> crossterm::Result<EditorEvent> {
         self.input.read()
     }
 
+    // Read a key for a prompt, which is not affected by the vi mode
+    fn read_key(&mut self) -> crossterm::Result<EditorEvent> {
+        self.input.read_plain()
+    }
+
+    pub fn mode(&self) -> Option<Mode> {
+        self.input.mode()
+    }
+
     pub fn clear(&mut self) -> crossterm::Result<()> {
         self.stdout
             .queue(terminal::Clear(terminal::ClearType::All))?
@@ -101,6 +249,8 @@ impl Screen {
 
     pub fn refresh(&mut self) -> crossterm::Result<()> {
         self.scroll();
+        // Clear with the background of the theme
+        self.set_style(self.theme.normal())?;
         self.clear()?;
         self.draw_rows()?;
         self.draw_status()?;
@@ -109,12 +259,88 @@ impl Screen {
     }
 
     pub fn toggle_line(&mut self) -> crossterm::Result<()> {
-        self.lno_width = if self.lno_width == 0 {
+        self.set_line_numbers(self.lno_width == 0);
+        self.refresh()
+    }
+
+    fn set_line_numbers(&mut self, show: bool) {
+        self.lno_width = if show {
             self.editrows.len().to_string().len() + LNO_WIDTH_EXTRA
         } else {
             0
         };
-        self.refresh()
+    }
+
+    /*
+     * Set an option of the set command: 'tabstop=N', 'shiftwidth=N' for
+     * the spaces of an indentation level, 'expandtab' to indent with
+     * spaces, 'number' and 'autopairs'. Options that are switched on and
+     * off take a 'no' prefix to switch them off.
+     */
+    pub fn set_option(&mut self, option: &str) -> Result<(), String> {
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => {
+                let value = value
+                    .parse::<u16>()
+                    .ok()
+                    .filter(|n| (1..=16).contains(n))
+                    .ok_or_else(|| format!("Invalid value: {}", option))?;
+                (name, Some(value))
+            }
+            None => (option, None),
+        };
+        let mut indentation = self.indentation;
+        match (name, value) {
+            ("tabstop" | "ts", Some(n)) => indentation.tabstop = n,
+            ("shiftwidth" | "sw", Some(n)) => match indentation.style {
+                IndentStyle::Spaces(_) => indentation.style = IndentStyle::Spaces(n),
+                IndentStyle::Tabs => return Err("shiftwidth needs expandtab".to_string()),
+            },
+            ("expandtab" | "et", None) => {
+                if indentation.style == IndentStyle::Tabs {
+                    indentation.style = IndentStyle::Spaces(indentation.tabstop);
+                }
+            }
+            ("noexpandtab" | "noet", None) => indentation.style = IndentStyle::Tabs,
+            ("number" | "nu", None) => self.set_line_numbers(true),
+            ("nonumber" | "nonu", None) => self.set_line_numbers(false),
+            ("autopairs", None) => self.auto_pairs = true,
+            ("noautopairs", None) => self.auto_pairs = false,
+            _ => return Err(format!("Unknown option: {}", option)),
+        }
+        self.set_indentation(indentation);
+        Ok(())
+    }
+
+    // The settings of the config file
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    // The options of the set command, as they are set
+    pub fn options(&self) -> String {
+        let mut options = vec![format!("tabstop={}", self.indentation.tabstop)];
+        match self.indentation.style {
+            IndentStyle::Spaces(n) => options.push(format!("expandtab shiftwidth={}", n)),
+            IndentStyle::Tabs => options.push("noexpandtab".to_string()),
+        }
+        options.push(
+            if self.lno_width == 0 {
+                "nonumber"
+            } else {
+                "number"
+            }
+            .to_string(),
+        );
+        options.push(
+            if self.auto_pairs {
+                "autopairs"
+            } else {
+                "noautopairs"
+            }
+            .to_string(),
+        );
+        options.join(" ")
     }
 
     /*
@@ -133,31 +359,70 @@ impl Screen {
      * Ctrl-@ = 0, so render it as an @ sign. Finally, any other nonprintable
      * characters is rendered as a question mark (?). And to differentiate
      * these characters from their printable counterparts, render them using
-     * inverted colors (black on white). use is_control() to check if the
+     * inverted colors of the current style. use is_control() to check if the
      * current character is a control character. If so,  translate it into
      * a printable one by adding its value to '@' (in ASCII, the uppercase
      * letters of the alphabet come after the @ character), or using the '?'
      * character if it's not in the alphabetic range.
      */
 
+    /*
+     * Brackets drawn with a style of their own: the bracket at the cursor and
+     * its match, and brackets without a partner in languages that balance them.
+     * They are given by window row, each with its render column. Brackets
+     * without a partner are worked out once after an edit, a match is only
+     * looked for in the rows shown.
+     */
+    fn bracket_marks(&mut self) -> Vec<Vec<(usize, Element)>> {
+        let (top, height) = (self.rowoff, self.window.height as usize);
+        let shown = top..(top + height).min(self.editrows.len());
+        let mut marks = vec![Vec::new(); height];
+        let mut mark = |rows: &[EditRow], pos: Position, element| {
+            let y = pos.y as usize;
+            if shown.contains(&y) {
+                marks[y - top].push((rows[y].cx_to_rx(pos.x) as usize, element));
+            }
+        };
+        if self.syntax.is_some_and(|s| s.flags & BRACKETS != 0) {
+            let unbalanced = self
+                .unbalanced
+                .get_or_insert_with(|| unbalanced(&self.editrows));
+            for &pos in unbalanced.iter() {
+                mark(&self.editrows, pos, Element::UnbalancedBracket);
+            }
+        }
+        if let Some((at, other)) = find_match(&self.editrows, self.cursor, shown.clone()) {
+            mark(&self.editrows, at, Element::MatchingBracket);
+            mark(&self.editrows, other, Element::MatchingBracket);
+        }
+        marks
+    }
+
     pub fn draw_rows(&mut self) -> crossterm::Result<()> {
+        let normal = self.theme.normal();
+        let marks = self.bracket_marks();
+        let selection = self.selection();
         for y in 0..self.window.height {
             let filerow = y as usize + self.rowoff;
             if filerow >= self.editrows.len() {
                 if self.editrows.is_empty() && y == self.window.height / 3 {
                     self.show_welcome(y)?;
                 } else {
+                    self.set_style(self.theme.style(Element::Tilde))?;
                     self.stdout
                         .queue(cursor::MoveTo(0_u16, y))?
                         .queue(style::Print("~".to_string()))?;
+                    self.set_style(normal)?;
                 }
             } else {
                 // Display line numbers
                 if self.lno_width > 0 {
                     let lno_str = format!("{0:>1$}", filerow + 1, self.lno_width - LNO_WIDTH_EXTRA);
+                    self.set_style(self.theme.style(Element::LineNumber))?;
                     self.stdout
                         .queue(cursor::MoveTo(0, y))?
                         .queue(style::Print(lno_str))?;
+                    self.set_style(normal)?;
                 }
 
                 let colstart = self.coloff;
@@ -175,11 +440,29 @@ impl Screen {
                 let colend = colstart + len;
                 let curr_row = self.editrows[filerow].render[colstart..colend].to_string();
                 let curr_highlight = self.editrows[filerow].highlight[colstart..colend].to_vec();
-                let mut curr_color = style::Color::Reset;
+                let mut curr_style = normal;
+                // Render columns of the row that are selected
+                let selected = match selection {
+                    Some((start, end)) if (start.y..=end.y).contains(&(filerow as u16)) => {
+                        let row = &self.editrows[filerow];
+                        let from = if filerow == start.y as usize {
+                            row.cx_to_rx(start.x) as usize
+                        } else {
+                            0
+                        };
+                        let to = if filerow == end.y as usize {
+                            row.cx_to_rx(end.x) as usize
+                        } else {
+                            usize::MAX
+                        };
+                        from..to
+                    }
+                    _ => 0..0,
+                };
 
                 self.stdout
                     .queue(cursor::MoveTo(self.lno_width as u16, y))?;
-                for (c, hl) in curr_row.chars().zip(curr_highlight) {
+                for (rx, (c, hl)) in curr_row.chars().zip(curr_highlight).enumerate() {
                     // Handle ascii control characters. See notes above.
                     if c.is_control() {
                         let ctrl = if (c as u8) < 26 {
@@ -190,29 +473,28 @@ impl Screen {
                         // Print the control character in the reverse style (fg/bg colors swapped)
                         self.stdout
                             .queue(style::SetAttribute(style::Attribute::Reverse))?
-                            .queue(style::Print(ctrl))?
-                            .queue(style::SetAttribute(style::Attribute::Reset))?;
-                        if curr_color != style::Color::Reset {
-                            // An attribute reset resets all formatting, so restore the current color
-                            self.stdout.queue(style::SetForegroundColor(curr_color))?;
+                            .queue(style::Print(ctrl))?;
+                        // An attribute reset resets all formatting, so restore the current style
+                        self.set_style(curr_style)?;
+                    } else {
+                        let mut style = self.theme.style(Element::Text(hl));
+                        if selected.contains(&(colstart + rx)) {
+                            style = self.theme.style(Element::Selection).over(&style);
                         }
-                    } else if hl.is_normal() {
-                        if curr_color != style::Color::Reset {
-                            self.stdout
-                                .queue(style::SetForegroundColor(style::Color::Reset))?;
-                            curr_color = style::Color::Reset;
+                        let mark = marks[y as usize].iter().find(|(x, _)| *x == colstart + rx);
+                        if let Some(&(_, element)) = mark {
+                            style = self.theme.style(element).over(&style);
                         }
-                    } else {
-                        let color = style::Color::from(hl);
-                        if color != curr_color {
-                            self.stdout.queue(style::SetForegroundColor(color))?;
-                            curr_color = color;
+                        if style != curr_style {
+                            self.set_style(style)?;
+                            curr_style = style;
                         }
                     }
                     self.stdout.queue(style::Print(c))?;
                 }
-                self.stdout
-                    .queue(style::SetForegroundColor(style::Color::Reset))?;
+                if curr_style != normal {
+                    self.set_style(normal)?;
+                }
             }
         }
         Ok(())
@@ -234,9 +516,18 @@ impl Screen {
         } else {
             "[no ft]".to_string()
         };
+        let mut mode = match self.input.mode() {
+            Some(mode) => format!("{} | ", mode.name()),
+            None => String::new(),
+        };
+        if self.input.is_recording() {
+            mode.insert_str(0, "REC | ");
+        }
         let msg_right = format!(
-            "{} {}/{}",
+            "{}{} | {} | {}/{}",
+            mode,
             file_type,
+            self.indentation.style,
             self.cursor.y + 1,
             self.editrows.len()
         );
@@ -256,13 +547,11 @@ impl Screen {
         }
         let status_msg = format!("{}{}", status_left, status_right);
 
-        let color_status = style::Colors::new(style::Color::Black, style::Color::White);
+        self.set_style(self.theme.style(Element::StatusBar))?;
         self.stdout
             .queue(cursor::MoveTo(0, self.window.height))?
-            .queue(style::SetColors(color_status))?
             .queue(style::Print(status_msg))?;
-
-        self.stdout.queue(style::ResetColor)?;
+        self.set_style(self.theme.normal())?;
 
         Ok(())
     }
@@ -273,7 +562,6 @@ impl Screen {
             return Ok(());
         }
 
-        let color_status = style::Colors::new(style::Color::Black, style::Color::White);
         let status_help: String = self
             .status_msg
             .chars()
@@ -284,14 +572,210 @@ impl Screen {
         let rem_len = status_help.len().max(self.window.width as usize) - (status_help.len());
         let status_help = status_help + &" ".repeat(rem_len);
 
+        self.set_style(self.theme.style(Element::MessageBar))?;
         self.stdout
-            .queue(style::SetColors(color_status))?
             .queue(cursor::MoveTo(0, self.window.height + 1))?
             .queue(style::Print(status_help))?;
-        self.stdout.queue(style::ResetColor)?;
+        self.set_style(self.theme.normal())?;
+        Ok(())
+    }
+
+    // Short help with the keys of the most important commands
+    fn help_text(&self) -> String {
+        HELP_COMMANDS
+            .iter()
+            .filter_map(|&name| {
+                let command = find_command(name)?;
+                let keys = self.input.keymap().keys_for(command.event);
+                Some(format!("{}: {}", keys_to_string(keys.first()?), name))
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /*
+     * Show all the commands with their key bindings. Movement keys scroll
+     * the list, any other key closes it.
+     */
+    pub fn show_help(&mut self) -> crossterm::Result<()> {
+        let mut lines = vec![format!(
+            "{:<28} {:<20} {}",
+            "Keys", "Command", "Description"
+        )];
+        for command in COMMANDS {
+            let keys: Vec<String> = self
+                .input
+                .keymap()
+                .keys_for(command.event)
+                .iter()
+                .map(|keys| keys_to_string(keys))
+                .collect();
+            lines.push(format!(
+                "{:<28} {:<20} {}",
+                keys.join(", "),
+                command.name,
+                command.help
+            ));
+        }
+        let height = self.window.height as usize;
+        let max_top = lines.len().saturating_sub(height);
+        let mut top = 0;
+
+        loop {
+            self.set_style(self.theme.normal())?;
+            self.clear()?;
+            for (y, line) in lines.iter().skip(top).take(height).enumerate() {
+                let line: String = line.chars().take(self.window.width as usize).collect();
+                self.stdout
+                    .queue(cursor::MoveTo(0, y as u16))?
+                    .queue(style::Print(line))?;
+            }
+            self.set_status("Key bindings - arrows to scroll, any other key to close");
+            self.draw_status()?;
+            self.draw_message()?;
+            self.flush()?;
+
+            match self.read_key()? {
+                EditorEvent::Cursor(CursorKey::Up) => top = top.saturating_sub(1),
+                EditorEvent::Cursor(CursorKey::Down) => top = (top + 1).min(max_top),
+                EditorEvent::Cursor(CursorKey::PageUp) => top = top.saturating_sub(height),
+                EditorEvent::Cursor(CursorKey::PageDown) => top = (top + height).min(max_top),
+                _ => break,
+            }
+        }
+        self.set_status("");
         Ok(())
     }
 
+    /*
+     * Let the user pick one of 'items' from a list drawn over the rows,
+     * starting at 'selected'. The arrow and page keys move the selection,
+     * Enter or one of the 'actions' picks it, Esc cancels. The item picked
+     * is returned with the key that picked it. With a 'rank' function, the
+     * text typed at the prompt below the list filters the items and orders
+     * them by how well they match, else the title is shown there.
+     */
+    pub fn pick(
+        &mut self,
+        title: &str,
+        items: &[String],
+        rank: Option<RankFn>,
+        actions: &[EditorEvent],
+        selected: usize,
+    ) -> crossterm::Result<Option<(usize, EditorEvent)>> {
+        let mut query = LineEditor::new();
+        let mut shown: Vec<usize> = (0..items.len()).collect();
+        let (mut selected, mut top) = (selected.min(items.len().saturating_sub(1)), 0);
+
+        loop {
+            let (width, height) = (self.window.width as usize, self.window.height as usize);
+            if selected < top {
+                top = selected;
+            } else if selected >= top + height {
+                top = selected + 1 - height;
+            }
+            self.set_style(self.theme.normal())?;
+            self.clear()?;
+            for (y, &item) in shown.iter().enumerate().skip(top).take(height) {
+                let line: String = items[item].chars().take(width).collect();
+                if y == selected {
+                    self.set_style(self.theme.style(Element::Selection))?;
+                }
+                self.stdout
+                    .queue(cursor::MoveTo(0, (y - top) as u16))?
+                    .queue(style::Print(format!("{:<width$}", line)))?;
+                self.set_style(self.theme.normal())?;
+            }
+            if rank.is_some() {
+                let prompt = format!("{} ({}/{}): ", title, shown.len(), items.len());
+                let prompt_len = prompt.chars().count();
+                let (text, x) = query.view(width.saturating_sub(prompt_len));
+                self.set_status(&format!("{}{}", prompt, text));
+                self.draw_status()?;
+                self.draw_message()?;
+                self.stdout.queue(cursor::MoveTo(
+                    (prompt_len + x) as u16,
+                    self.window.height + 1,
+                ))?;
+            } else {
+                self.set_status(title);
+                self.draw_status()?;
+                self.draw_message()?;
+            }
+            self.flush()?;
+
+            let text = query.text();
+            match self.read_key()? {
+                event
+                    if event == EditorEvent::Cursor(CursorKey::Enter)
+                        || actions.contains(&event) =>
+                {
+                    if let Some(&item) = shown.get(selected) {
+                        self.set_status("");
+                        return Ok(Some((item, event)));
+                    }
+                }
+                EditorEvent::Control(ControlEvent::Escape) => break,
+                EditorEvent::Cursor(CursorKey::Up) => selected = selected.saturating_sub(1),
+                EditorEvent::Cursor(CursorKey::Down) => {
+                    selected = (selected + 1).min(shown.len().saturating_sub(1))
+                }
+                EditorEvent::Cursor(CursorKey::PageUp) => {
+                    selected = selected.saturating_sub(height)
+                }
+                EditorEvent::Cursor(CursorKey::PageDown) => {
+                    selected = (selected + height).min(shown.len().saturating_sub(1))
+                }
+                event => {
+                    if let Some(rank) = rank {
+                        query.edit(event);
+                        if query.text() != text {
+                            shown = rank(&query.text(), items);
+                            (selected, top) = (0, 0);
+                        }
+                    }
+                }
+            }
+        }
+        self.set_status("");
+        Ok(None)
+    }
+
+    // Show why a command failed and note the failure
+    pub fn fail(&mut self, message: &str) {
+        self.failed = true;
+        self.set_status(message);
+    }
+
+    // Check if a command failed since the last call
+    pub fn take_failed(&mut self) -> bool {
+        std::mem::take(&mut self.failed)
+    }
+
+    pub fn edits(&self) -> usize {
+        self.edits
+    }
+
+    pub fn start_recording(&mut self) {
+        self.input.start_recording();
+    }
+
+    pub fn stop_recording(&mut self) -> Option<Vec<EditorEvent>> {
+        self.input.stop_recording()
+    }
+
+    pub fn play(&mut self, events: &[EditorEvent]) {
+        self.input.play(events);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.input.is_playing()
+    }
+
+    pub fn stop_playing(&mut self) {
+        self.input.stop_playing();
+    }
+
     pub fn set_status(&mut self, message: &str) {
         self.status_time = time::Instant::now();
         self.status_msg = message.to_string();
@@ -302,9 +786,12 @@ impl Screen {
         welcome.truncate(self.window.width as usize);
         if welcome.len() < self.window.width as usize {
             let left = ((self.window.width as usize - welcome.len() - self.lno_width) / 2) as u16;
+            self.set_style(self.theme.style(Element::Tilde))?;
             self.stdout
                 .queue(cursor::MoveTo(0_u16, row))?
-                .queue(style::Print("~".to_string()))?
+                .queue(style::Print("~".to_string()))?;
+            self.set_style(self.theme.normal())?;
+            self.stdout
                 .queue(cursor::MoveTo(left + self.lno_width as u16, row))?
                 .queue(style::Print(welcome))?;
         } else {
@@ -318,70 +805,143 @@ impl Screen {
     /*
      * prompts user for an input string and returns an Ok(Some(String)) or an
      * Ok(None) if the prompt is cancelled. It can also return an error.
+     *
+     * The input is edited like a row, the Up and Down arrows go through the
+     * lines entered before at the same kind of prompt. Tab completes the
+     * line, or with a callback moves to the next match, Shift-Tab to the
+     * previous one.
      */
     pub fn show_prompt(
         &mut self,
         prompt: &str,
+        kind: PromptKind,
+        callback: Option<PromptCallback>,
+    ) -> crossterm::Result<Option<String>> {
+        self.show_prompt_with(prompt, kind, callback, "")
+    }
+
+    // Prompt with 'text' typed already
+    pub fn show_prompt_with(
+        &mut self,
+        prompt: &str,
+        kind: PromptKind,
         callback: Option<PromptCallback>,
+        text: &str,
     ) -> crossterm::Result<Option<String>> {
-        let mut buf = String::new();
+        let prompt = if prompt.ends_with(':') {
+            prompt.to_string()
+        } else {
+            format!("{}: ", prompt)
+        };
+        let prompt_len = prompt.chars().count();
+        let mut line = LineEditor::new();
+        line.set(text);
+        let mut completions: Vec<String> = Vec::new();
+        let mut completion = 0;
+        self.history(kind).reset();
 
         loop {
-            self.set_status(&format!("{}: {}", prompt, buf));
-            self.refresh()?;
-            self.flush()?;
+            let width = (self.window.width as usize).saturating_sub(prompt_len);
+            let (text, x) = line.view(width);
+            self.set_status(&format!("{}{}", prompt, text));
+            // Don't draw the keys of a macro that is playing
+            if !self.input.is_playing() {
+                self.refresh()?;
+                self.stdout.queue(cursor::MoveTo(
+                    (prompt_len + x) as u16,
+                    self.window.height + 1,
+                ))?;
+                self.flush()?;
+            }
 
-            match self.read() {
-                Ok(event) => {
-                    match event {
-                        EditorEvent::Cursor(CursorKey::Enter) => {
-                            self.set_status("");
-                            Self::do_callback(self, callback, &buf, event);
-                            return Ok(Some(buf));
-                        }
-                        EditorEvent::Key(ch) => {
-                            if Input::is_valid_file_char(ch) {
-                                buf.push(ch);
-                            }
-                        }
-                        EditorEvent::Cursor(CursorKey::Backspace)
-                        | EditorEvent::Cursor(CursorKey::Delete) => {
-                            buf.pop();
-                        }
-                        EditorEvent::Control(ControlEvent::Escape) => {
-                            self.set_status("");
-                            Self::do_callback(self, callback, &buf, event);
-                            return Ok(None);
-                        }
-                        EditorEvent::Cursor(CursorKey::Right)
-                        | EditorEvent::Cursor(CursorKey::Down) => {
-                            Self::do_callback(
-                                self,
-                                callback,
-                                &buf,
-                                EditorEvent::Cursor(CursorKey::Right),
-                            );
-                            continue;
-                        }
-                        EditorEvent::Cursor(CursorKey::Left)
-                        | EditorEvent::Cursor(CursorKey::Up) => {
-                            Self::do_callback(
-                                self,
-                                callback,
-                                &buf,
-                                EditorEvent::Cursor(CursorKey::Left),
-                            );
-                            continue;
-                        }
-                        _ => {}
-                    }
+            let Ok(event) = self.read_key() else {
+                continue;
+            };
+            if event != EditorEvent::Key('\t') {
+                completions.clear();
+            }
+            let buf = line.text();
+            match event {
+                EditorEvent::Cursor(CursorKey::Enter) => {
+                    self.set_status("");
+                    self.history(kind).add(&buf);
+                    Self::do_callback(self, callback, &buf, event);
+                    return Ok(Some(buf));
+                }
+                EditorEvent::Control(ControlEvent::Escape) => {
+                    self.set_status("");
                     Self::do_callback(self, callback, &buf, event);
+                    return Ok(None);
+                }
+                EditorEvent::Key('\t') if callback.is_none() => {
+                    if completions.is_empty() {
+                        completions = kind.complete(&buf);
+                        completion = 0;
+                    }
+                    if let Some(text) = completions.get(completion) {
+                        line.set(text);
+                        completion = (completion + 1) % completions.len();
+                    }
+                    // A single completion, like a directory, is completed further
+                    if completions.len() == 1 {
+                        completions.clear();
+                    }
+                }
+                EditorEvent::Key('\t') | EditorEvent::Control(ControlEvent::Find) => {
+                    let next = EditorEvent::Cursor(CursorKey::Right);
+                    Self::do_callback(self, callback, &buf, next);
+                    continue;
+                }
+                EditorEvent::Control(ControlEvent::Dedent) => {
+                    let previous = EditorEvent::Cursor(CursorKey::Left);
+                    Self::do_callback(self, callback, &buf, previous);
+                    continue;
+                }
+                EditorEvent::Control(ControlEvent::Paste) => {
+                    let text: String = self.input.pasted().split('\n').collect();
+                    line.insert_str(&text);
+                }
+                EditorEvent::Control(ControlEvent::Yank) => {
+                    // The last text cut in emacs mode, or deleted or yanked in vi mode
+                    let text = self.kill_ring.current().unwrap_or(&self.register.text);
+                    line.insert_str(text);
+                }
+                EditorEvent::Cursor(CursorKey::Up) => {
+                    if let Some(text) = self.history(kind).older(&buf) {
+                        line.set(text);
+                    }
+                }
+                EditorEvent::Cursor(CursorKey::Down) => {
+                    if let Some(text) = self.history(kind).newer() {
+                        line.set(text);
+                    }
+                }
+                event => {
+                    line.edit(event);
                 }
-                Err(_e) => {}
+            }
+            // Only changes of the text concern the callback
+            let text = line.text();
+            if text != buf {
+                Self::do_callback(self, callback, &text, event);
             }
         }
     }
 
+    // Ask a question to answer with 'y' or 'n', any other key answers no
+    pub fn confirm(&mut self, question: &str) -> crossterm::Result<bool> {
+        self.set_status(&format!("{} (y/n)", question));
+        self.refresh()?;
+        self.flush()?;
+        let event = self.read_key()?;
+        self.set_status("");
+        Ok(matches!(event, EditorEvent::Key('y' | 'Y')))
+    }
+
+    fn history(&mut self, kind: PromptKind) -> &mut History {
+        self.histories.entry(kind).or_insert_with(History::new)
+    }
+
     fn do_callback(&mut self, callback: Option<PromptCallback>, buf: &str, event: EditorEvent) {
         if let Some(callback) = callback {
             callback(self, buf, event);
@@ -416,6 +976,18 @@ impl Screen {
     }
 
     pub fn move_cursor(&mut self, key: CursorKey) {
+        // Typing after moving the cursor is a new undo step
+        if !matches!(
+            key,
+            CursorKey::Delete
+                | CursorKey::Backspace
+                | CursorKey::Enter
+                | CursorKey::DeleteWordLeft
+                | CursorKey::DeleteWordRight
+        ) {
+            self.undo.seal();
+        }
+        let (x, y) = (self.cursor.x, self.cursor.y);
         match key {
             CursorKey::Left => {
                 if self.cursor.x != 0 {
@@ -481,6 +1053,52 @@ impl Screen {
             }
             CursorKey::Backspace => self.delete_char(),
             CursorKey::Enter => self.insert_newline(),
+            CursorKey::WordLeft => {
+                let cy = self.cursor.y as usize;
+                if self.cursor.x == 0 || cy >= self.editrows.len() {
+                    self.move_cursor(CursorKey::Left);
+                } else {
+                    self.cursor.x = self.editrows[cy].word_left(self.cursor.x as usize) as u16;
+                }
+            }
+            CursorKey::WordRight => {
+                let cy = self.cursor.y as usize;
+                if cy < self.editrows.len()
+                    && (self.cursor.x as usize) < self.editrows[cy].chars.chars().count()
+                {
+                    self.cursor.x = self.editrows[cy].word_right(self.cursor.x as usize) as u16;
+                } else {
+                    self.move_cursor(CursorKey::Right);
+                }
+            }
+            CursorKey::FileStart => {
+                self.cursor.x = 0;
+                self.cursor.y = 0;
+            }
+            CursorKey::FileEnd => {
+                self.cursor.y = self.editrows.len().saturating_sub(1) as u16;
+                self.move_cursor(CursorKey::End);
+            }
+            CursorKey::DeleteWordLeft => {
+                let cy = self.cursor.y as usize;
+                if self.cursor.x == 0 || cy >= self.editrows.len() {
+                    self.delete_char();
+                } else {
+                    let start = self.editrows[cy].word_left(self.cursor.x as usize);
+                    self.delete_chars(start, self.cursor.x as usize);
+                }
+            }
+            CursorKey::DeleteWordRight => {
+                let cy = self.cursor.y as usize;
+                if cy < self.editrows.len()
+                    && (self.cursor.x as usize) < self.editrows[cy].chars.chars().count()
+                {
+                    let end = self.editrows[cy].word_right(self.cursor.x as usize);
+                    self.delete_chars(self.cursor.x as usize, end);
+                } else {
+                    self.move_cursor(CursorKey::Delete);
+                }
+            }
         }
         // Find the number of characters on the editrow
         let rowlen = if self.cursor.y as usize >= self.editrows.len() {
@@ -490,6 +1108,20 @@ impl Screen {
         };
         self.cursor.x = self.cursor.x.min(rowlen);
         self.update_syntax_ml(self.cursor.y as usize);
+
+        // Moving against the start or end of the file fails
+        let moves = matches!(
+            key,
+            CursorKey::Left
+                | CursorKey::Right
+                | CursorKey::Up
+                | CursorKey::Down
+                | CursorKey::WordLeft
+                | CursorKey::WordRight
+        );
+        if moves && (x, y) == (self.cursor.x, self.cursor.y) {
+            self.failed = true;
+        }
     }
 
     /*
@@ -528,71 +1160,1285 @@ impl Screen {
         }
     }
 
-    pub fn insert_char(&mut self, ch: char) {
-        if (self.cursor.y as usize) == self.editrows.len() {
-            self.insert_row(self.editrows.len(), "", false);
+    // Move the cursor while keeping the other end of the selection in place
+    pub fn select(&mut self, key: CursorKey) {
+        if self.anchor.is_none() {
+            self.anchor = Some(self.cursor);
         }
-        let cy = self.cursor.y as usize;
-        let cx = self.cursor.x as usize;
-
-        self.editrows[cy].insert_char(cx, ch);
-        self.cursor.x += 1;
-        self.set_dirty(true);
-        self.update_syntax_ml(cy);
+        self.move_cursor(key);
     }
 
-    // Delete character left of the cursor
-    pub fn delete_char(&mut self) {
-        let cy = self.cursor.y as usize;
-        let cx = self.cursor.x as usize;
-
-        if cx == 0 && cy == 0 || cy >= self.editrows.len() {
-            return;
-        }
-        let s = self.editrows[cy].chars.clone();
-        if cx > 0 {
-            self.editrows[cy].delete_char(cx - 1);
-            self.cursor.x = (cx - 1) as u16;
-        } else {
-            self.cursor.x = self.editrows[cy - 1].chars.len() as u16;
-            self.editrows[cy - 1].append_str(&s);
-            self.delete_row(cy);
-            self.cursor.y -= 1;
-        }
-        self.set_dirty(true);
+    pub fn clear_selection(&mut self) {
+        self.anchor = None;
     }
 
-    pub fn insert_row(&mut self, at: usize, s: &str, open_comment: bool) {
-        if at > self.editrows.len() {
-            return;
+    // Start and end of the selection in file order, None if nothing is selected
+    fn selection(&self) -> Option<(Position, Position)> {
+        let anchor = self.anchor?;
+        let (a, b) = ((anchor.y, anchor.x), (self.cursor.y, self.cursor.x));
+        match a.cmp(&b) {
+            cmp::Ordering::Less => Some((anchor, self.cursor)),
+            cmp::Ordering::Greater => Some((self.cursor, anchor)),
+            cmp::Ordering::Equal => None,
         }
-        self.editrows
-            .insert(at, EditRow::new(s.to_string(), open_comment));
     }
 
-    pub fn insert_newline(&mut self) {
-        let cy = self.cursor.y as usize;
-        let cx = self.cursor.x as usize;
-        // if cursor is at the beginning, just insert a new row at the current row index,
-        // else split the current row. Either way increment 'y' and set 'x' to 0.
-        let open_comment = if cy == 0 || self.editrows.is_empty() {
-            false
-        } else if cy == self.editrows.len() {
-            self.editrows[cy - 1].open_ml_comment
-        } else {
-            self.editrows[cy].open_ml_comment
+    /*
+     * The rows the selection spans, or the row of the cursor without one. A
+     * selection that ends at the start of a row doesn't include that row.
+     */
+    fn selected_rows(&self) -> Option<(usize, usize)> {
+        let (first, last) = match self.selection() {
+            Some((start, end)) if end.x == 0 && end.y > start.y => {
+                (start.y as usize, end.y as usize - 1)
+            }
+            Some((start, end)) => (start.y as usize, end.y as usize),
+            None => (self.cursor.y as usize, self.cursor.y as usize),
         };
-        if cx == 0 {
-            self.insert_row(cy, "", open_comment)
-        } else {
-            let new_row = self.editrows[cy].split(cx);
-            self.editrows.insert(cy + 1, new_row);
-        }
-        self.set_dirty(true);
-        // Since an extra row is created, highlight that as well.
-        self.update_syntax_ml(cy);
-        self.cursor.y += 1;
-        self.cursor.x = 0;
+        let last = last.min(self.editrows.len().checked_sub(1)?);
+        (first <= last).then_some((first, last))
+    }
+
+    // The rows the selection spans, None without a selection
+    pub fn selected_lines(&self) -> Option<(usize, usize)> {
+        self.anchor?;
+        self.selected_rows()
+    }
+
+    // Save the 'len' rows from 'at' before an edit replaces them
+    fn begin_change(&self, at: usize, len: usize) -> Change {
+        let end = (at + len).min(self.editrows.len());
+        let old = self.editrows[at.min(end)..end]
+            .iter()
+            .map(|row| row.chars.clone())
+            .collect();
+        Change::new(at, old, self.cursor, self.editrows.len())
+    }
+
+    // Record the edit that began with begin_change(), so that it can be undone
+    fn end_change(&mut self, mut change: Change) {
+        let len = change.old.len() + self.editrows.len() - change.rows_before;
+        change.new = self.editrows[change.at..change.at + len]
+            .iter()
+            .map(|row| row.chars.clone())
+            .collect();
+        change.cursor_after = self.cursor;
+        self.undo.push(change);
+        self.edits += 1;
+    }
+
+    fn apply_change(&mut self, change: Change) {
+        let end = (change.at + change.old.len()).min(self.editrows.len());
+        let tabstop = self.indentation.tabstop;
+        let rows = change
+            .new
+            .iter()
+            .map(|chars| EditRow::new(chars.to_string(), false, tabstop));
+        self.editrows.splice(change.at..end, rows);
+        self.cursor = change.cursor_after;
+        self.anchor = None;
+        self.edits += 1;
+        self.set_dirty(true);
+        self.update_syntax_rows(change.at, change.at + change.new.len());
+    }
+
+    pub fn undo(&mut self) {
+        match self.undo.undo() {
+            Some(change) => self.apply_change(change),
+            None => self.fail("Nothing to undo"),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        match self.undo.redo() {
+            Some(change) => self.apply_change(change),
+            None => self.fail("Nothing to redo"),
+        }
+    }
+
+    /*
+     * Comment or uncomment the selected rows, or the row of the cursor. The
+     * rows are uncommented if all of them are comments already. The single
+     * line marker of the language is put at the smallest indentation of
+     * the rows, the block markers around all of them if there is none.
+     */
+    pub fn toggle_comment(&mut self) {
+        let comment = match self.syntax {
+            Some(syntax) => &syntax.comment,
+            None => {
+                self.set_status("No comment markers without a syntax");
+                return;
+            }
+        };
+        let Some((first, last)) = self.selected_rows() else {
+            return;
+        };
+        let rows: Vec<String> = self.editrows[first..=last]
+            .iter()
+            .map(|row| row.chars.clone())
+            .collect();
+        let toggled = if let Some(marker) = comment.single.first() {
+            Self::toggle_line_comment(&rows, marker)
+        } else if let Some((start, end)) = &comment.multiline {
+            Self::toggle_block_comment(&rows, start, end)
+        } else {
+            self.set_status("No comment markers for this syntax");
+            return;
+        };
+
+        let change = self.begin_change(first, rows.len());
+        for (at, chars) in (first..=last).zip(toggled) {
+            if at == self.cursor.y as usize {
+                let len = self.editrows[at].chars.len() as i32;
+                let x = self.cursor.x as i32 + chars.len() as i32 - len;
+                self.cursor.x = x.clamp(0, chars.len() as i32) as u16;
+            }
+            self.editrows[at].set_chars(&chars);
+        }
+        self.anchor = self.anchor.map(|mut anchor| {
+            anchor.x = anchor
+                .x
+                .min(self.editrows[anchor.y as usize].chars.len() as u16);
+            anchor
+        });
+        self.set_dirty(true);
+        self.update_syntax_rows(first, last);
+        self.end_change(change);
+    }
+
+    fn toggle_line_comment(rows: &[String], marker: &str) -> Vec<String> {
+        let code_rows = || rows.iter().filter(|row| !row.trim().is_empty());
+        let commented = code_rows().all(|row| row.trim_start().starts_with(marker));
+        let indent = code_rows()
+            .map(|row| row.len() - row.trim_start().len())
+            .min()
+            .unwrap_or(0);
+
+        rows.iter()
+            .map(|row| {
+                if row.trim().is_empty() {
+                    row.clone()
+                } else if commented {
+                    let text = row.trim_start();
+                    let uncommented = &text[marker.len()..];
+                    let uncommented = uncommented.strip_prefix(' ').unwrap_or(uncommented);
+                    format!("{}{}", &row[..row.len() - text.len()], uncommented)
+                } else {
+                    format!("{}{} {}", &row[..indent], marker, &row[indent..])
+                }
+            })
+            .collect()
+    }
+
+    fn toggle_block_comment(rows: &[String], start: &str, end: &str) -> Vec<String> {
+        let mut rows = rows.to_vec();
+        let first = rows.iter().position(|row| !row.trim().is_empty());
+        let last = rows.iter().rposition(|row| !row.trim().is_empty());
+        let (Some(first), Some(last)) = (first, last) else {
+            return rows;
+        };
+        let commented = rows[first].trim_start().starts_with(start)
+            && rows[last].trim_end().ends_with(end)
+            && (first != last || rows[first].trim().len() >= start.len() + end.len());
+
+        let indent = rows[first].len() - rows[first].trim_start().len();
+        if commented {
+            let text = &rows[first][indent + start.len()..];
+            let text = text.strip_prefix(' ').unwrap_or(text);
+            rows[first] = format!("{}{}", &rows[first][..indent], text);
+            let row = rows[last].trim_end();
+            let text = &row[..row.len() - end.len()];
+            rows[last] = text.strip_suffix(' ').unwrap_or(text).to_string();
+        } else {
+            rows[first].insert_str(indent, &format!("{} ", start));
+            rows[last].push_str(&format!(" {}", end));
+        }
+        rows
+    }
+
+    // Duplicate the selected lines or the line of the cursor below them
+    pub fn duplicate_lines(&mut self) {
+        let Some((first, last)) = self.selected_rows() else {
+            return;
+        };
+        let len = last + 1 - first;
+        let change = self.begin_change(first, len);
+        let copies = self.editrows[first..=last].to_vec();
+        self.editrows.splice(last + 1..last + 1, copies);
+        self.cursor.y += len as u16;
+        if let Some(anchor) = &mut self.anchor {
+            anchor.y += len as u16;
+        }
+        self.set_dirty(true);
+        self.update_syntax_rows(last + 1, last + len);
+        self.end_change(change);
+    }
+
+    // Move the selected lines or the line of the cursor one line up or down
+    pub fn move_lines(&mut self, up: bool) {
+        let Some((first, last)) = self.selected_rows() else {
+            return;
+        };
+        if (up && first == 0) || (!up && last + 1 >= self.editrows.len()) {
+            return;
+        }
+        let (from, to) = if up {
+            (first - 1, last)
+        } else {
+            (first, last + 1)
+        };
+        let change = self.begin_change(from, to + 1 - from);
+        if up {
+            self.editrows[from..=to].rotate_left(1);
+            self.cursor.y -= 1;
+        } else {
+            self.editrows[from..=to].rotate_right(1);
+            self.cursor.y += 1;
+        }
+        if let Some(anchor) = &mut self.anchor {
+            anchor.y = if up { anchor.y - 1 } else { anchor.y + 1 };
+        }
+        self.set_dirty(true);
+        self.update_syntax_rows(from, to);
+        self.end_change(change);
+    }
+
+    /*
+     * Join the next line onto the line of the cursor, or all the selected
+     * lines into one. The whitespace where two lines meet becomes a single
+     * space, or nothing if either side is empty.
+     */
+    pub fn join_lines(&mut self) {
+        let Some((first, last)) = self.selected_rows() else {
+            return;
+        };
+        let last = if last == first { first + 1 } else { last };
+        if last >= self.editrows.len() {
+            return;
+        }
+        let change = self.begin_change(first, last + 1 - first);
+        let mut joined = self.editrows[first].chars.trim_end().to_string();
+        let mut join_x = joined.len();
+        for row in self.editrows.drain(first + 1..=last) {
+            let text = row.chars.trim();
+            join_x = joined.len();
+            if !joined.trim().is_empty() && !text.is_empty() {
+                joined.push(' ');
+            }
+            joined.push_str(text);
+        }
+        self.editrows[first].set_chars(&joined);
+        self.cursor.y = first as u16;
+        self.cursor.x = join_x as u16;
+        self.anchor = None;
+        self.set_dirty(true);
+        self.update_syntax_ml(first);
+        self.end_change(change);
+    }
+
+    // Delete the selected lines or the line of the cursor
+    pub fn delete_lines(&mut self) {
+        let Some((first, last)) = self.selected_rows() else {
+            return;
+        };
+        let change = self.begin_change(first, last + 1 - first);
+        self.editrows.drain(first..=last);
+        self.cursor.y = first as u16;
+        let len = self.editrows.get(first).map_or(0, |row| row.chars.len());
+        self.cursor.x = self.cursor.x.min(len as u16);
+        self.anchor = None;
+        self.set_dirty(true);
+        self.update_syntax_ml(first);
+        self.end_change(change);
+    }
+
+    // Prompt for a position and move the cursor there
+    pub fn goto_prompt(&mut self) -> crossterm::Result<()> {
+        if let Some(spec) =
+            self.show_prompt("Go to (line[:col], +N, -N, N%)", PromptKind::Goto, None)?
+        {
+            let current = self.cursor.y as usize + 1;
+            match parse_goto(&spec, current, self.editrows.len()) {
+                Some(goto) => self.goto(goto),
+                None => self.fail(&format!("Invalid position '{}'", spec.trim())),
+            }
+        }
+        Ok(())
+    }
+
+    // Move the cursor to a position and show its line in the middle of the window
+    pub fn goto(&mut self, goto: Goto) {
+        if self.editrows.is_empty() {
+            return;
+        }
+        let y = (goto.line - 1).min(self.editrows.len() - 1);
+        let len = self.editrows[y].chars.len();
+        self.cursor.y = y as u16;
+        self.cursor.x = goto.col.map_or(0, |col| (col.max(1) - 1).min(len)) as u16;
+        self.rowoff = y.saturating_sub(self.window.height as usize / 2);
+    }
+
+    // Scroll so that the line of the cursor is in the middle of the window
+    pub fn recenter(&mut self) {
+        self.rowoff = (self.cursor.y as usize).saturating_sub(self.window.height as usize / 2);
+    }
+
+    // Remember the event the editor handled last, kills and yanks depend on it
+    pub fn set_last_event(&mut self, event: EditorEvent) {
+        self.last_event = Some(event);
+    }
+
+    pub fn set_mark(&mut self) {
+        self.anchor = Some(self.cursor);
+        self.set_status("Mark set");
+    }
+
+    // Add killed text to the kill ring, following kills are added to the same text
+    fn kill(&mut self, text: &str) {
+        let append = matches!(
+            self.last_event,
+            Some(EditorEvent::Control(
+                ControlEvent::KillLine | ControlEvent::KillRegion
+            ))
+        );
+        self.kill_ring.kill(text, append);
+    }
+
+    // Kill the rest of the line, or the line break at the end of the line
+    pub fn kill_line(&mut self) {
+        let cy = self.cursor.y as usize;
+        if cy >= self.editrows.len() {
+            return;
+        }
+        let len = self.row_len(cy);
+        let end = if (self.cursor.x as usize) < len {
+            Position::new(len as u16, cy as u16)
+        } else if cy + 1 < self.editrows.len() {
+            Position::new(0, cy as u16 + 1)
+        } else {
+            return;
+        };
+        let start = self.cursor;
+        let text = self.text_between(start, end);
+        self.kill(&text);
+        self.replace_text(start, end, "");
+    }
+
+    pub fn kill_region(&mut self) {
+        match self.selection() {
+            Some((start, end)) => {
+                let text = self.text_between(start, end);
+                self.kill(&text);
+                self.replace_text(start, end, "");
+            }
+            None => self.fail("The mark is not set"),
+        }
+    }
+
+    pub fn copy_region(&mut self) {
+        match self.selection() {
+            Some((start, end)) => {
+                let text = self.text_between(start, end);
+                self.kill(&text);
+                self.anchor = None;
+                self.set_status("Region copied");
+            }
+            None => self.fail("The mark is not set"),
+        }
+    }
+
+    // Insert the text killed last at the cursor
+    pub fn yank(&mut self) {
+        let Some(text) = self.kill_ring.current().map(String::from) else {
+            self.fail("Kill ring is empty");
+            return;
+        };
+        let start = self.cursor;
+        self.replace_text(start, start, &text);
+        self.last_yank = Some((start, self.cursor));
+    }
+
+    // Insert the text pasted in the terminal at the cursor, as one change
+    pub fn paste(&mut self) {
+        let text = self.input.pasted().to_string();
+        let start = self.cursor;
+        self.replace_text(start, start, &text);
+    }
+
+    // Replace the text just yanked with the text killed before it
+    pub fn yank_pop(&mut self) {
+        let yanked = matches!(
+            self.last_event,
+            Some(EditorEvent::Control(
+                ControlEvent::Yank | ControlEvent::YankPop
+            ))
+        );
+        let (Some((start, end)), true) = (self.last_yank, yanked) else {
+            self.fail("Previous command was not a yank");
+            return;
+        };
+        let Some(text) = self.kill_ring.rotate().map(String::from) else {
+            return;
+        };
+        self.replace_text(start, end, &text);
+        self.last_yank = Some((start, self.cursor));
+    }
+
+    // Move the cursor to the bracket matching the one at the cursor
+    pub fn jump_to_match(&mut self) {
+        match find_match(&self.editrows, self.cursor, 0..self.editrows.len()) {
+            Some((_, other)) => {
+                self.cursor.x = other.x;
+                self.cursor.y = other.y;
+            }
+            None => self.fail("No matching bracket"),
+        }
+    }
+
+    fn row_len(&self, y: usize) -> usize {
+        self.editrows
+            .get(y)
+            .map_or(0, |row| row.chars.chars().count())
+    }
+
+    fn indent_len(&self, y: usize) -> usize {
+        self.editrows
+            .get(y)
+            .map_or(0, |row| row.indentation().len())
+    }
+
+    // Split a string at a character index
+    fn split_chars(s: &str, x: usize) -> (&str, &str) {
+        let at = s.char_indices().nth(x).map_or(s.len(), |(i, _)| i);
+        s.split_at(at)
+    }
+
+    // The text from 'start' up to 'end', with rows separated by '\n'
+    fn text_between(&self, start: Position, end: Position) -> String {
+        let mut lines = Vec::new();
+        for y in start.y as usize..=end.y as usize {
+            let Some(row) = self.editrows.get(y) else {
+                break;
+            };
+            let mut text = row.chars.as_str();
+            if y == end.y as usize {
+                text = Self::split_chars(text, end.x as usize).0;
+            }
+            if y == start.y as usize {
+                text = Self::split_chars(text, start.x as usize).1;
+            }
+            lines.push(text);
+        }
+        lines.join("\n")
+    }
+
+    /*
+     * Replace the text from 'start' up to 'end' with 'text', which may span
+     * several rows, as one change. The cursor moves to the end of the text.
+     */
+    fn replace_text(&mut self, start: Position, end: Position, text: &str) {
+        let first = start.y as usize;
+        let change = self.begin_change(first, end.y as usize + 1 - first);
+        if first >= self.editrows.len() {
+            self.insert_row(self.editrows.len(), "", false);
+        }
+        let last = (end.y as usize).min(self.editrows.len() - 1);
+        let prefix = Self::split_chars(&self.editrows[first].chars, start.x as usize).0;
+        let suffix = Self::split_chars(&self.editrows[last].chars, end.x as usize).1;
+        let mut lines: Vec<String> = format!("{}{}", prefix, text)
+            .split('\n')
+            .map(String::from)
+            .collect();
+        let x = lines.last().map_or(0, |line| line.chars().count());
+        let suffix = suffix.to_string();
+        if let Some(line) = lines.last_mut() {
+            line.push_str(&suffix);
+        }
+
+        let len = lines.len();
+        let tabstop = self.indentation.tabstop;
+        let rows = lines
+            .into_iter()
+            .map(|line| EditRow::new(line, false, tabstop));
+        self.editrows.splice(first..=last, rows);
+        self.cursor.y = (first + len - 1) as u16;
+        self.cursor.x = x as u16;
+        self.anchor = None;
+        self.set_dirty(true);
+        self.update_syntax_rows(first, first + len - 1);
+        self.end_change(change);
+    }
+
+    /*
+     * Replace rows 'first' to 'last' with 'rows' as one change. The cursor
+     * stays on its line, or the last one if there are fewer lines now.
+     */
+    pub fn replace_rows(&mut self, first: usize, last: usize, rows: &[String]) {
+        let change = self.begin_change(first, last + 1 - first);
+        let tabstop = self.indentation.tabstop;
+        let rows = rows
+            .iter()
+            .map(|row| EditRow::new(row.to_string(), false, tabstop));
+        let len = rows.len();
+        let end = (last + 1).min(self.editrows.len());
+        self.editrows.splice(first.min(end)..end, rows);
+        let y = (self.cursor.y as usize).min(self.editrows.len().saturating_sub(1));
+        self.cursor.y = y as u16;
+        self.cursor.x = self.cursor.x.min(self.row_len(y) as u16);
+        self.anchor = None;
+        self.set_dirty(true);
+        self.update_syntax_rows(first, (first + len).max(1) - 1);
+        self.end_change(change);
+    }
+
+    /*
+     * Replace all the rows with the reformatted 'rows' as one change. The
+     * cursor stays on the same line of the text, at the same place of it
+     * if only its whitespace changed, and at the same place on screen.
+     */
+    pub fn reformat(&mut self, rows: &[String]) {
+        let old: Vec<String> = self.editrows.iter().map(|row| row.chars.clone()).collect();
+        let (y, x) = (self.cursor.y as usize, self.cursor.x as usize);
+        let new_y = map_line(&old, rows, y);
+        let new_x = match (old.get(y), rows.get(new_y)) {
+            (Some(old_row), Some(new_row)) if squeeze(old_row) == squeeze(new_row) => {
+                map_col(old_row, new_row, x)
+            }
+            _ => x,
+        };
+        self.replace_rows(0, old.len().saturating_sub(1), rows);
+        self.rowoff = (self.rowoff + new_y).saturating_sub(y);
+        self.cursor.y = new_y.min(self.editrows.len().saturating_sub(1)) as u16;
+        self.cursor.x = new_x.min(self.row_len(self.cursor.y as usize)) as u16;
+    }
+
+    /*
+     * Replace the first match of 'pattern' on each of the rows 'first' to
+     * 'last', or all of the matches if 'global', as one change. Return the
+     * number of replacements.
+     */
+    pub fn substitute(
+        &mut self,
+        first: usize,
+        last: usize,
+        pattern: &Regex,
+        replacement: &str,
+        global: bool,
+    ) -> usize {
+        let last = last.min(self.editrows.len().saturating_sub(1));
+        let first = first.min(last);
+        let mut count = 0;
+        let rows: Vec<String> = self
+            .editrows
+            .get(first..=last)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|row| {
+                let matches = pattern.find_iter(&row.chars).count();
+                let limit = if global { 0 } else { 1 };
+                count += if global { matches } else { matches.min(1) };
+                // A newline in the replacement splits the row
+                let text = pattern.replacen(&row.chars, limit, replacement);
+                text.split('\n').map(String::from).collect::<Vec<String>>()
+            })
+            .collect();
+        if count == 0 {
+            self.fail(&format!("Pattern not found: {}", pattern));
+        } else {
+            self.replace_rows(first, last, &rows);
+            self.set_status(&format!(
+                "{} substitution(s) on lines {} to {}",
+                count,
+                first + 1,
+                last + 1
+            ));
+        }
+        count
+    }
+
+    // Sort the rows 'first' to 'last' as one change
+    pub fn sort_lines(&mut self, first: usize, last: usize, reverse: bool) {
+        let last = last.min(self.editrows.len().saturating_sub(1));
+        if first >= last {
+            return;
+        }
+        let mut rows: Vec<String> = self.editrows[first..=last]
+            .iter()
+            .map(|row| row.chars.clone())
+            .collect();
+        rows.sort();
+        if reverse {
+            rows.reverse();
+        }
+        self.replace_rows(first, last, &rows);
+        self.set_status(&format!("Sorted {} lines", rows.len()));
+    }
+
+    /*
+     * Where a vi motion repeated 'count' times moves from 'pos'. Left and
+     * right stay on the row, up and down keep the column where possible.
+     */
+    fn vi_target(&self, pos: Position, motion: Motion, count: usize) -> Position {
+        let last_row = self.editrows.len().saturating_sub(1);
+        let (mut x, mut y) = (pos.x as usize, pos.y as usize);
+        match motion {
+            Motion::Left => x = x.saturating_sub(count),
+            Motion::Right => x = x.saturating_add(count),
+            Motion::Up => y = y.saturating_sub(count),
+            Motion::Down => y = y.saturating_add(count).min(last_row),
+            Motion::Lines => y = (y + count.saturating_sub(1)).min(last_row),
+            Motion::LineStart => x = 0,
+            Motion::FirstNonBlank => x = self.indent_len(y),
+            Motion::LineEnd => {
+                y = (y + count.saturating_sub(1)).min(last_row);
+                x = self.row_len(y);
+            }
+            Motion::GotoLine(line) => {
+                y = line.map_or(last_row, |line| line.saturating_sub(1).min(last_row));
+                x = self.indent_len(y);
+            }
+            Motion::WordStart => {
+                for _ in 0..count {
+                    x = self.editrows.get(y).map_or(0, |row| row.word_right(x));
+                    if x >= self.row_len(y) && y < last_row {
+                        y += 1;
+                        x = self.indent_len(y);
+                    }
+                }
+            }
+            Motion::WordBack => {
+                for _ in 0..count {
+                    let at_start = self
+                        .editrows
+                        .get(y)
+                        .is_none_or(|row| row.chars.chars().take(x).all(char::is_whitespace));
+                    if at_start && y > 0 {
+                        y -= 1;
+                        x = self.row_len(y);
+                    }
+                    x = self.editrows.get(y).map_or(0, |row| row.word_left(x));
+                }
+            }
+            Motion::WordEnd => {
+                for _ in 0..count {
+                    let mut from = x + 1;
+                    loop {
+                        match self.editrows.get(y).and_then(|row| row.word_end(from)) {
+                            Some(end) => {
+                                x = end;
+                                break;
+                            }
+                            None if y < last_row => {
+                                y += 1;
+                                from = 0;
+                            }
+                            None => break,
+                        }
+                    }
+                }
+            }
+        }
+        Position::new(x.min(self.row_len(y)) as u16, y as u16)
+    }
+
+    // Run a vi command other than ':'
+    pub fn vi_command(&mut self, command: ViCommand) {
+        match command {
+            ViCommand::Move(motion, count) => {
+                self.undo.seal();
+                let mut target = self.vi_target(self.cursor, motion, count);
+                if motion != Motion::LineEnd {
+                    let len = self.row_len(target.y as usize);
+                    target.x = target.x.min(len.saturating_sub(1) as u16);
+                }
+                let moves = !matches!(
+                    motion,
+                    Motion::LineStart
+                        | Motion::FirstNonBlank
+                        | Motion::LineEnd
+                        | Motion::GotoLine(_)
+                );
+                if moves && (target.x, target.y) == (self.cursor.x, self.cursor.y) {
+                    self.failed = true;
+                }
+                self.cursor.x = target.x;
+                self.cursor.y = target.y;
+            }
+            ViCommand::Operate(op, motion, count) => self.vi_operate(op, motion, count),
+            ViCommand::Insert(at) => self.vi_insert(at),
+            ViCommand::NormalMode => {
+                self.undo.seal();
+                self.cursor.x = self.cursor.x.saturating_sub(1);
+            }
+            ViCommand::Visual => self.anchor = Some(self.cursor),
+            ViCommand::VisualOperate(op) => {
+                let (start, mut end) = self.selection().unwrap_or((self.cursor, self.cursor));
+                // Both ends of the selection are part of it in vi
+                end.x = (end.x as usize + 1).min(self.row_len(end.y as usize)) as u16;
+                self.vi_apply(op, start, end, false);
+            }
+            ViCommand::Paste(before, count) => self.vi_paste(before, count),
+            ViCommand::Command => {}
+        }
+    }
+
+    // Keep the cursor on a character, as vi does outside of insert mode
+    pub fn keep_cursor_on_char(&mut self) {
+        let len = self.row_len(self.cursor.y as usize);
+        if len > 0 && self.cursor.x as usize >= len {
+            self.cursor.x = (len - 1) as u16;
+        }
+    }
+
+    fn vi_operate(&mut self, op: Operator, motion: Motion, count: usize) {
+        let cy = self.cursor.y as usize;
+        let on_word = self
+            .editrows
+            .get(cy)
+            .and_then(|row| row.chars.chars().nth(self.cursor.x as usize))
+            .is_some_and(|c| !c.is_whitespace());
+        let mut inclusive = motion == Motion::WordEnd;
+        let target = if op == Operator::Change && motion == Motion::WordStart && on_word {
+            // 'cw' changes up to the end of the word like 'ce', keeping the space after it
+            inclusive = true;
+            let x = self.editrows[cy].word_end(self.cursor.x as usize);
+            let end = Position::new(x.unwrap_or(0) as u16, cy as u16);
+            self.vi_target(end, Motion::WordEnd, count - 1)
+        } else {
+            self.vi_target(self.cursor, motion, count)
+        };
+
+        let (start, mut end) = if (target.y, target.x) < (self.cursor.y, self.cursor.x) {
+            (target, self.cursor)
+        } else {
+            (self.cursor, target)
+        };
+        if matches!(
+            motion,
+            Motion::Up | Motion::Down | Motion::Lines | Motion::GotoLine(_)
+        ) {
+            self.vi_apply(op, start, end, true);
+            return;
+        }
+        if inclusive {
+            end.x = (end.x as usize + 1).min(self.row_len(end.y as usize)) as u16;
+        } else if end.y > start.y && (motion == Motion::WordStart || end.x == 0) {
+            // A word motion ends at the end of its row instead of the next one
+            end.y -= 1;
+            end.x = self.row_len(end.y as usize) as u16;
+        }
+        self.vi_apply(op, start, end, false);
+    }
+
+    // Delete, change or yank the text from 'start' up to 'end', or the rows of both if linewise
+    fn vi_apply(&mut self, op: Operator, start: Position, end: Position, linewise: bool) {
+        self.anchor = None;
+        if self.editrows.is_empty() {
+            return;
+        }
+        if !linewise {
+            self.register = Register {
+                text: self.text_between(start, end),
+                linewise: false,
+            };
+            match op {
+                Operator::Yank => self.cursor = start,
+                Operator::Delete | Operator::Change => self.replace_text(start, end, ""),
+            }
+            return;
+        }
+
+        let first = start.y as usize;
+        let last = (end.y as usize).min(self.editrows.len() - 1);
+        let rows: Vec<&str> = self.editrows[first..=last]
+            .iter()
+            .map(|row| row.chars.as_str())
+            .collect();
+        self.register = Register {
+            text: rows.join("\n"),
+            linewise: true,
+        };
+        let len = last + 1 - first;
+        match op {
+            Operator::Yank => {
+                self.cursor.y = first as u16;
+                if len > 1 {
+                    self.set_status(&format!("{} lines yanked", len));
+                }
+            }
+            Operator::Delete => {
+                let change = self.begin_change(first, len);
+                self.editrows.drain(first..=last);
+                let y = first.min(self.editrows.len().saturating_sub(1));
+                self.cursor.y = y as u16;
+                self.cursor.x = self.indent_len(y) as u16;
+                self.set_dirty(true);
+                self.update_syntax_ml(y);
+                self.end_change(change);
+            }
+            Operator::Change => {
+                let indent = self.editrows[first].indentation().to_string();
+                let change = self.begin_change(first, len);
+                let row = EditRow::new(indent.clone(), false, self.indentation.tabstop);
+                self.editrows.splice(first..=last, [row]);
+                self.cursor.y = first as u16;
+                self.cursor.x = indent.len() as u16;
+                self.set_dirty(true);
+                self.update_syntax_ml(first);
+                self.end_change(change);
+            }
+        }
+    }
+
+    fn vi_insert(&mut self, at: InsertAt) {
+        self.undo.seal();
+        let cy = self.cursor.y as usize;
+        match at {
+            InsertAt::Cursor => {}
+            InsertAt::After => {
+                self.cursor.x = (self.cursor.x as usize + 1).min(self.row_len(cy)) as u16;
+            }
+            InsertAt::LineStart => self.cursor.x = self.indent_len(cy) as u16,
+            InsertAt::LineEnd => self.cursor.x = self.row_len(cy) as u16,
+            InsertAt::LineBelow => {
+                self.move_cursor(CursorKey::End);
+                self.insert_newline();
+            }
+            InsertAt::LineAbove => {
+                let indent = self
+                    .editrows
+                    .get(cy)
+                    .map_or(String::new(), |row| row.indentation().to_string());
+                let change = self.begin_change(cy, 0);
+                self.insert_row(cy, &indent, false);
+                self.cursor.x = indent.len() as u16;
+                self.set_dirty(true);
+                self.update_syntax_ml(cy);
+                self.end_change(change);
+            }
+        }
+    }
+
+    // Put the text of the register after or before the cursor, 'count' times
+    fn vi_paste(&mut self, before: bool, count: usize) {
+        if self.register.text.is_empty() {
+            self.fail("Nothing to paste");
+            return;
+        }
+        let separator = if self.register.linewise { "\n" } else { "" };
+        let text = vec![self.register.text.as_str(); count].join(separator);
+        let cy = self.cursor.y as usize;
+
+        if self.register.linewise {
+            let y = if before {
+                cy
+            } else {
+                (cy + 1).min(self.editrows.len())
+            };
+            let change = self.begin_change(y, 0);
+            let tabstop = self.indentation.tabstop;
+            let rows: Vec<EditRow> = text
+                .split('\n')
+                .map(|line| EditRow::new(line.to_string(), false, tabstop))
+                .collect();
+            let len = rows.len();
+            self.editrows.splice(y..y, rows);
+            self.cursor.y = y as u16;
+            self.cursor.x = self.indent_len(y) as u16;
+            self.set_dirty(true);
+            self.update_syntax_rows(y, y + len - 1);
+            self.end_change(change);
+        } else {
+            let len = self.row_len(cy);
+            let x = if before || len == 0 {
+                self.cursor.x as usize
+            } else {
+                self.cursor.x as usize + 1
+            };
+            let pos = Position::new(x.min(len) as u16, cy as u16);
+            self.replace_text(pos, pos, &text);
+            // The cursor ends on the last character put in
+            self.cursor.x = self.cursor.x.saturating_sub(1);
+        }
+    }
+
+    pub fn insert_char(&mut self, ch: char) {
+        let cy = self.cursor.y as usize;
+        let cx = self.cursor.x as usize;
+        if cy < self.editrows.len() && self.auto_pairs && self.skips_closer(cy, cx, ch) {
+            self.cursor.x += 1;
+            return;
+        }
+        let change = self.begin_change(cy, 1).typing();
+        if cy == self.editrows.len() {
+            self.insert_row(self.editrows.len(), "", false);
+        }
+
+        if ch == '\t' {
+            if let IndentStyle::Spaces(width) = self.indentation.style {
+                // Insert spaces up to the next multiple of the indent width
+                let rx = self.editrows[cy].cx_to_rx(cx as u16);
+                let spaces = width - rx % width;
+                self.editrows[cy].insert_str(cx, &" ".repeat(spaces as usize));
+                self.cursor.x += spaces;
+                self.set_dirty(true);
+                self.update_syntax_ml(cy);
+                self.end_change(change);
+                return;
+            }
+        }
+        let closer = if self.auto_pairs {
+            self.auto_closer(cy, cx, ch)
+        } else {
+            None
+        };
+        let completes_closer = self.syntax.is_some_and(|syntax| {
+            let (before, after) = self.editrows[cy].split_at_char(cx);
+            syntax.indent.completes_closer(before, ch, after)
+        });
+        self.editrows[cy].insert_char(cx, ch);
+        if let Some(closer) = closer {
+            self.editrows[cy].insert_char(cx + 1, closer);
+        }
+        self.cursor.x += 1;
+        if completes_closer {
+            self.dedent_closer(cy);
+        }
+        self.set_dirty(true);
+        self.update_syntax_ml(cy);
+        self.end_change(change);
+    }
+
+    pub fn toggle_auto_pairs(&mut self) {
+        self.auto_pairs = !self.auto_pairs;
+        let state = if self.auto_pairs { "on" } else { "off" };
+        self.set_status(&format!("Auto pairs {}", state));
+    }
+
+    /*
+     * Check if the position is inside a string or a comment, going by the
+     * highlight of the character before it. Right after the closing quote
+     * of a string or the end of a multiline comment is outside.
+     */
+    fn in_string_or_comment(&self, cy: usize, cx: usize) -> bool {
+        if cx == 0 {
+            return cy > 0 && self.editrows[cy - 1].open_ml_comment;
+        }
+        let row = &self.editrows[cy];
+        let chars: Vec<char> = row.chars.chars().collect();
+        let hl = |cx: usize| row.highlight.get(row.cx_to_rx(cx as u16) as usize).copied();
+
+        match hl(cx - 1) {
+            Some(Highlight::Comment) => {
+                let before: String = chars[..cx].iter().collect();
+                let ml_end = self.syntax.and_then(|s| s.comment.multiline.as_ref());
+                !ml_end.is_some_and(|(_, end)| before.ends_with(end.as_str()))
+            }
+            Some(Highlight::Str) => {
+                let start = (0..cx)
+                    .rev()
+                    .find(|&i| hl(i) != Some(Highlight::Str))
+                    .map_or(0, |i| i + 1);
+                let quote = chars[start];
+                let closed = cx - 1 > start && chars[cx - 1] == quote && chars[cx - 2] != '\\';
+                !closed
+            }
+            _ => false,
+        }
+    }
+
+    /*
+     * The character that closes 'ch' when it is typed at the position. Pairs
+     * are closed only in code, and only before whitespace, a closing bracket
+     * or the end of the line. Quotes need a syntax with strings and are not
+     * closed after a word, where they are more likely an apostrophe.
+     */
+    fn auto_closer(&self, cy: usize, cx: usize, ch: char) -> Option<char> {
+        let chars: Vec<char> = self.editrows[cy].chars.chars().collect();
+        let next = chars.get(cx).copied();
+        if self.in_string_or_comment(cy, cx)
+            || next.is_some_and(|c| !c.is_whitespace() && !")]}".contains(c))
+        {
+            return None;
+        }
+        if QUOTES.contains(&ch) {
+            let strings = self.syntax.is_some_and(|s| s.flags & STRINGS != 0);
+            let after_word = cx > 0 && Number::is_ident_char(chars[cx - 1]);
+            return (strings && !after_word).then_some(ch);
+        }
+        match bracket_pair(ch) {
+            Some((open, close)) if ch == open => Some(close),
+            _ => None,
+        }
+    }
+
+    // Check if typing 'ch' should step over the same closing character at the position
+    fn skips_closer(&self, cy: usize, cx: usize, ch: char) -> bool {
+        let row = &self.editrows[cy];
+        if row.chars.chars().nth(cx) != Some(ch) {
+            return false;
+        }
+        if QUOTES.contains(&ch) {
+            // The closing quote of the string the cursor is in
+            let rx = row.cx_to_rx(cx as u16) as usize;
+            row.highlight.get(rx) == Some(&Highlight::Str) && self.in_string_or_comment(cy, cx)
+        } else {
+            bracket_pair(ch).is_some_and(|(_, close)| ch == close)
+                && !self.in_string_or_comment(cy, cx)
+        }
+    }
+
+    // Check if the cursor is between an empty pair like '()' or '""'
+    fn in_empty_pair(&self, cy: usize, cx: usize) -> bool {
+        let chars: Vec<char> = self.editrows[cy].chars.chars().collect();
+        if cx == 0 || cx >= chars.len() {
+            return false;
+        }
+        let (prev, next) = (chars[cx - 1], chars[cx]);
+        if QUOTES.contains(&prev) {
+            prev == next
+        } else {
+            bracket_pair(prev) == Some((prev, next)) && !self.in_string_or_comment(cy, cx - 1)
+        }
+    }
+
+    /*
+     * Dedent a line that closes a block, like '}', by one level. Do it only if
+     * the line is indented at least as much as the line before, so that a line
+     * which was already dedented is left alone.
+     */
+    fn dedent_closer(&mut self, cy: usize) {
+        let indent = self.editrows[cy].indentation().to_string();
+        let prev_indent = self.editrows[..cy]
+            .iter()
+            .rev()
+            .find(|row| !row.chars.trim().is_empty())
+            .map_or(0, |row| row.indentation().len());
+        if indent.is_empty() || indent.len() < prev_indent {
+            return;
+        }
+        self.dedent_row(cy);
+    }
+
+    // Remove one level of indentation from a row and return the number of removed characters
+    fn dedent_row(&mut self, at: usize) -> usize {
+        let indent = self.editrows[at].indentation();
+        let remove = if indent.starts_with('\t') {
+            1
+        } else {
+            let width = match self.indentation.style {
+                IndentStyle::Spaces(width) => width,
+                IndentStyle::Tabs => self.indentation.tabstop,
+            };
+            let spaces = indent.len() - indent.trim_start_matches(' ').len();
+            spaces.min(width as usize)
+        };
+        if remove > 0 {
+            let chars = self.editrows[at].chars[remove..].to_string();
+            self.editrows[at].set_chars(&chars);
+            if at == self.cursor.y as usize {
+                self.cursor.x = self.cursor.x.saturating_sub(remove as u16);
+            }
+        }
+        remove
+    }
+
+    /*
+     * Indent or dedent the rows from 'first' to 'last' by one level. Empty
+     * rows are not indented, so that they don't end with whitespace.
+     */
+    pub fn indent_rows(&mut self, first: usize, last: usize, dedent: bool) {
+        let last = last.min(self.editrows.len().saturating_sub(1));
+        let unit = self.indentation.style.unit();
+        let change = self.begin_change(first, last + 1 - first);
+        let mut changed = false;
+        for at in first..=last {
+            if dedent {
+                changed |= self.dedent_row(at) > 0;
+            } else if !self.editrows[at].chars.is_empty() {
+                self.editrows[at].insert_str(0, &unit);
+                if at == self.cursor.y as usize {
+                    self.cursor.x += unit.len() as u16;
+                }
+                changed = true;
+            }
+        }
+        if changed {
+            self.set_dirty(true);
+            self.update_syntax_rows(first, last);
+            self.end_change(change);
+        }
+    }
+
+    // Indent or dedent the line of the cursor
+    pub fn indent_line(&mut self, dedent: bool) {
+        let cy = self.cursor.y as usize;
+        if cy < self.editrows.len() {
+            self.indent_rows(cy, cy, dedent);
+        }
+    }
+
+    // Indent or dedent the selected rows or the block of non-empty lines around the cursor
+    pub fn indent_block(&mut self, dedent: bool) {
+        if self.selection().is_some() {
+            if let Some((first, last)) = self.selected_rows() {
+                self.indent_rows(first, last, dedent);
+            }
+            return;
+        }
+        let cy = self.cursor.y as usize;
+        if cy >= self.editrows.len() || self.editrows[cy].chars.trim().is_empty() {
+            return;
+        }
+        let is_blank = |row: &EditRow| row.chars.trim().is_empty();
+        let first = self.editrows[..cy]
+            .iter()
+            .rposition(is_blank)
+            .map_or(0, |at| at + 1);
+        let last = self.editrows[cy..]
+            .iter()
+            .position(is_blank)
+            .map_or(self.editrows.len(), |at| cy + at)
+            - 1;
+        self.indent_rows(first, last, dedent);
+    }
+
+    // Delete the characters from 'start' to 'end' on the row of the cursor
+    fn delete_chars(&mut self, start: usize, end: usize) {
+        let cy = self.cursor.y as usize;
+        let change = self.begin_change(cy, 1);
+        let chars: String = self.editrows[cy]
+            .chars
+            .chars()
+            .enumerate()
+            .filter(|(cx, _)| !(start..end).contains(cx))
+            .map(|(_, c)| c)
+            .collect();
+        self.editrows[cy].set_chars(&chars);
+        self.cursor.x = start as u16;
+        self.set_dirty(true);
+        self.update_syntax_ml(cy);
+        self.end_change(change);
+    }
+
+    // Delete character left of the cursor
+    pub fn delete_char(&mut self) {
+        let cy = self.cursor.y as usize;
+        let cx = self.cursor.x as usize;
+
+        if cx == 0 && cy == 0 || cy >= self.editrows.len() {
+            return;
+        }
+        let change = if cx > 0 {
+            self.begin_change(cy, 1).typing()
+        } else {
+            self.begin_change(cy - 1, 2)
+        };
+        let s = self.editrows[cy].chars.clone();
+        let in_indent = s[..cx.min(s.len())].bytes().all(|b| b == b' ');
+        if self.auto_pairs && self.in_empty_pair(cy, cx) {
+            self.editrows[cy].set_chars(&format!("{}{}", &s[..cx - 1], &s[cx + 1..]));
+            self.cursor.x = (cx - 1) as u16;
+        } else if let (IndentStyle::Spaces(width), true) =
+            (self.indentation.style, in_indent && cx > 0)
+        {
+            // Remove spaces back to the previous indentation level
+            let remove = match cx % width as usize {
+                0 => width as usize,
+                n => n,
+            };
+            self.editrows[cy].set_chars(&format!("{}{}", &s[..cx - remove], &s[cx..]));
+            self.cursor.x = (cx - remove) as u16;
+        } else if cx > 0 {
+            self.editrows[cy].delete_char(cx - 1);
+            self.cursor.x = (cx - 1) as u16;
+        } else {
+            self.cursor.x = self.editrows[cy - 1].chars.len() as u16;
+            self.editrows[cy - 1].append_str(&s);
+            self.delete_row(cy);
+            self.cursor.y -= 1;
+        }
+        self.set_dirty(true);
+        self.end_change(change);
+    }
+
+    pub fn insert_row(&mut self, at: usize, s: &str, open_comment: bool) {
+        if at > self.editrows.len() {
+            return;
+        }
+        self.editrows.insert(
+            at,
+            EditRow::new(s.to_string(), open_comment, self.indentation.tabstop),
+        );
+    }
+
+    pub fn insert_newline(&mut self) {
+        let cy = self.cursor.y as usize;
+        let cx = self.cursor.x as usize;
+        // if cursor is at the beginning, just insert a new row at the current row index,
+        // else split the current row and indent the new one. Either way increment 'y'.
+        let open_comment = if cy == 0 || self.editrows.is_empty() {
+            false
+        } else if cy == self.editrows.len() {
+            self.editrows[cy - 1].open_ml_comment
+        } else {
+            self.editrows[cy].open_ml_comment
+        };
+        let change = self.begin_change(cy, 1);
+        let mut new_rows = 1;
+        // Enter right after a word that closes a block, like 'fi', completes it
+        let completes_closer = cx > 0
+            && self.syntax.is_some_and(|syntax| {
+                let (before, after) = self.editrows[cy].split_at_char(cx);
+                syntax.indent.completes_closer(before, '\n', after)
+            });
+        if completes_closer {
+            self.dedent_closer(cy);
+        }
+        let cx = self.cursor.x as usize;
+        if cx == 0 {
+            self.insert_row(cy, "", open_comment);
+            self.cursor.x = 0;
+        } else {
+            /* The new line gets the indentation of the current one and one more
+             * level if the current line opens a block. If the rest of the line
+             * closes that block, like Enter within '{}', it goes on a line of
+             * its own with the old indentation.
+             */
+            let indent = self.editrows[cy].indentation().to_string();
+            let indent = indent[..indent.len().min(cx)].to_string();
+            let code = self.editrows[cy].code(cx);
+            let right = self.editrows[cy].split(cx);
+            let rest = right.chars.trim_start();
+            let mut new_indent = indent.clone();
+            let mut closing = None;
+            if let Some(syntax) = self.syntax {
+                if syntax.indent.opens_block(&code) {
+                    new_indent.push_str(&self.indentation.style.unit());
+                    if syntax.indent.starts_with_closer(rest) {
+                        closing = Some(format!("{}{}", indent, rest));
+                    }
+                }
+            }
+            // Don't leave the whitespace of an empty line behind
+            if self.editrows[cy].chars.trim().is_empty() {
+                self.editrows[cy].set_chars("");
+            }
+            let open_comment = self.editrows[cy].open_ml_comment;
+            if let Some(closing) = closing {
+                self.insert_row(cy + 1, &new_indent, open_comment);
+                self.insert_row(cy + 2, &closing, open_comment);
+                new_rows = 2;
+            } else {
+                self.insert_row(cy + 1, &format!("{}{}", new_indent, rest), open_comment);
+            }
+            self.cursor.x = new_indent.len() as u16;
+        }
+        self.set_dirty(true);
+        // Since extra rows are created, highlight those as well.
+        self.update_syntax_rows(cy, cy + new_rows);
+        self.cursor.y += 1;
+        self.end_change(change);
     }
 
     pub fn delete_row(&mut self, at: usize) {
@@ -607,6 +2453,12 @@ impl Screen {
      * Highlight all the rows in the file based on syntax
      */
     fn update_syntax_all(&mut self) {
+        self.unbalanced = None;
+        #[cfg(feature = "treesitter")]
+        if let Some(treesitter) = &mut self.treesitter {
+            treesitter.update_all(&mut self.editrows);
+            return;
+        }
         let mut state = RenderState::new();
 
         for row in self.editrows.iter_mut() {
@@ -615,6 +2467,18 @@ impl Screen {
         }
     }
 
+    // Highlight the rows from 'first' to 'last' after they were changed
+    fn update_syntax_rows(&mut self, first: usize, last: usize) {
+        #[cfg(feature = "treesitter")]
+        if self.treesitter.is_some() {
+            self.update_syntax_ml(first);
+            return;
+        }
+        for y in first..=last {
+            self.update_syntax_ml(y);
+        }
+    }
+
     /*
      * Highlight text for the remaining lines in a multiline comment
      * Use the 'open_ml_comment' flag of the previous line to start
@@ -624,6 +2488,11 @@ impl Screen {
         if !self.is_dirty() || cy >= self.editrows.len() {
             return;
         }
+        #[cfg(feature = "treesitter")]
+        if let Some(treesitter) = &mut self.treesitter {
+            treesitter.update(&mut self.editrows);
+            return;
+        }
         let mut i = cy;
         let mut state = RenderState::new();
         state.prev_in_ml_comment = if i == 0 {
@@ -643,21 +2512,32 @@ impl Screen {
     }
 
     pub fn find(&mut self) -> crossterm::Result<()> {
+        // A macro searches from the cursor, so that each run goes on where the last one ended
+        self.search_info.from_cursor = self.input.is_playing() || self.input.is_recording();
+        if self.search_info.from_cursor {
+            self.search_info.last_match = None;
+        }
         let saved_cursor = self.cursor;
         let saved_coloff = self.coloff;
         let saved_rowoff = self.rowoff;
 
-        if let Some(query) =
-            self.show_prompt("Search (ESC/Arrows/Enter)", Some(Self::find_callback))?
-        {
+        if let Some(query) = self.show_prompt(
+            "Search (Esc/Tab/Shift-Tab/Enter)",
+            PromptKind::Search,
+            Some(Self::find_callback),
+        )? {
             if !self.find_callback(&query, EditorEvent::Cursor(CursorKey::Enter)) {
-                self.set_status(&format!("Could not find '{}'", query));
+                self.fail(&format!("Could not find '{}'", query));
             }
         } else {
             self.cursor = saved_cursor;
             self.coloff = saved_coloff;
             self.rowoff = saved_rowoff;
-            self.set_status("Cancelled search");
+            if self.search_info.from_cursor {
+                self.fail("Cancelled search");
+            } else {
+                self.set_status("Cancelled search");
+            }
         }
         self.restore_highlight();
         Ok(())
@@ -681,23 +2561,44 @@ impl Screen {
             _ => {}
         }
 
-        let mut current = if let Some(last_match) = self.search_info.last_match {
-            last_match
-        } else {
-            self.search_info.direction = SearchDirection::Forwards;
-            0
+        if self.editrows.is_empty() {
+            return false;
+        }
+        let arrows = matches!(
+            event,
+            EditorEvent::Cursor(CursorKey::Left) | EditorEvent::Cursor(CursorKey::Right)
+        );
+        /* A search of a macro starts after the cursor, typing more of the
+         * query keeps the match at the cursor if it still matches. Either way
+         * the first row is searched from the column of the cursor. Other
+         * searches start at the top.
+         */
+        let from_cursor = self.search_info.from_cursor;
+        let cy = (self.cursor.y as usize).min(self.editrows.len() - 1);
+        let rx = self.editrows[cy].cx_to_rx(self.cursor.x) as usize;
+        let (first, mut from) = match self.search_info.last_match {
+            Some(last_match) if from_cursor && !arrows => (last_match, rx),
+            Some(last_match) => (last_match, 0),
+            None => {
+                self.search_info.direction = SearchDirection::Forwards;
+                if from_cursor {
+                    (cy, rx + 1)
+                } else {
+                    (0, 0)
+                }
+            }
         };
-        /* Perform 'editrows.len()' iterations so that each row is searched for
-         * atleast once but not necessarily in the order of its indices, but
-         * starting with index 'current', moving forward, wrapping back to 0
-         * when 'current' reaches the end. Set the cursor to the matched string
-         * and return when upon finding a match.
+        let mut current = first;
+
+        /* Perform 'editrows.len() + 1' iterations so that each row is searched
+         * for atleast once, the first one again from its start, but not
+         * necessarily in the order of its indices, but starting with index
+         * 'current', moving forward, wrapping back to 0 when 'current'
+         * reaches the end. Set the cursor to the matched string and return
+         * when upon finding a match.
          */
-        for _ in self.editrows.iter() {
-            if matches!(
-                event,
-                EditorEvent::Cursor(CursorKey::Left) | EditorEvent::Cursor(CursorKey::Right)
-            ) {
+        for i in 0..=self.editrows.len() {
+            if arrows {
                 // Change cursor only when arrow keys are used for next/prev search
                 match self.search_info.direction {
                     SearchDirection::Forwards => {
@@ -715,10 +2616,17 @@ impl Screen {
                         };
                     }
                 }
+                from = 0;
             }
 
             // If a match was found
-            if let Some(rx) = self.editrows[current].render.find(query) {
+            let render = &self.editrows[current].render;
+            if let Some(rx) = render.get(from..).and_then(|rest| rest.find(query)) {
+                // Wrapping around the end of the file stops a macro
+                if from_cursor && !arrows && (current < first || (current == first && i > 0)) {
+                    self.failed = true;
+                }
+                let rx = from + rx;
                 self.search_info.last_match = Some(current);
                 self.cursor.y = current as u16;
                 self.cursor.x = self.editrows[current].rx_to_cx(rx as u16);
@@ -727,18 +2635,14 @@ impl Screen {
                 self.search_info.saved_highlight = Some(SavedHighlight::new(current, saved_hl));
                 self.editrows[current].highlight_match(rx, query.len());
                 return true;
-            } else {
+            } else if !arrows {
                 // If no match was found, try the next row
-                if !matches!(
-                    event,
-                    EditorEvent::Cursor(CursorKey::Left) | EditorEvent::Cursor(CursorKey::Right)
-                ) {
-                    current = if current >= (self.editrows.len() - 1) {
-                        0
-                    } else {
-                        current + 1
-                    };
-                }
+                current = if current >= (self.editrows.len() - 1) {
+                    0
+                } else {
+                    current + 1
+                };
+                from = 0;
             }
         }
         false
@@ -751,12 +2655,40 @@ impl Screen {
         }
     }
 
+    /*
+     * Move the cursor to 'len' characters at 'line' and 'col', counted from
+     * 0, and highlight them like a search match until clear_match.
+     */
+    pub fn show_match(&mut self, line: usize, col: usize, len: usize) {
+        self.restore_highlight();
+        self.goto(Goto {
+            line: line + 1,
+            col: Some(col + 1),
+        });
+        let Some(row) = self.editrows.get_mut(line) else {
+            return;
+        };
+        let col = col.min(row.chars.len());
+        let end = (col + len).min(row.chars.len());
+        let rx = row.cx_to_rx(col as u16) as usize;
+        let len = row.cx_to_rx(end as u16) as usize - rx;
+        self.search_info.saved_highlight = Some(SavedHighlight::new(line, row.highlight.clone()));
+        row.highlight_match(rx, len);
+    }
+
+    pub fn clear_match(&mut self) {
+        self.restore_highlight();
+    }
+
     pub fn is_dirty(&mut self) -> bool {
         self.dirty
     }
 
     pub fn set_dirty(&mut self, dirty: bool) {
         self.dirty = dirty;
+        if dirty {
+            self.unbalanced = None;
+        }
     }
 
     pub fn dec_quit_times(&mut self) -> u8 {
@@ -768,6 +2700,26 @@ impl Screen {
         self.quit_times = QUIT_TIMES;
     }
 
+    pub fn rows(&self) -> usize {
+        self.editrows.len()
+    }
+
+    // Number of lines, without the empty row after a newline that ends the file
+    pub fn lines(&self) -> usize {
+        match self.editrows.last() {
+            Some(row) if row.chars.is_empty() => self.editrows.len() - 1,
+            _ => self.editrows.len(),
+        }
+    }
+
+    // The text of the rows 'first' to 'last', a newline after each one
+    pub fn rows_text(&self, first: usize, last: usize) -> String {
+        self.editrows[first..=last]
+            .iter()
+            .map(|row| format!("{}\n", row.chars))
+            .collect()
+    }
+
     pub fn rows_to_string(&self) -> String {
         self.editrows
             .iter()
@@ -777,7 +2729,9 @@ impl Screen {
     }
 
     pub fn release(&mut self) -> crossterm::Result<()> {
+        let _ = self.stdout.queue(style::ResetColor);
         let _ = self.clear();
+        let _ = self.stdout.execute(event::DisableBracketedPaste);
         terminal::disable_raw_mode()
     }
 }
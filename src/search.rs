@@ -9,6 +9,7 @@ pub struct SearchInfo {
     pub last_match: Option<usize>,
     pub direction: SearchDirection,
     pub saved_highlight: Option<SavedHighlight>,
+    pub from_cursor: bool, // search from the cursor instead of the top, as in a macro
 }
 
 impl SearchInfo {
@@ -17,6 +18,7 @@ impl SearchInfo {
             last_match: None,
             direction: SearchDirection::Forwards,
             saved_highlight: None,
+            from_cursor: false,
         }
     }
 }
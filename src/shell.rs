@@ -0,0 +1,77 @@
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+use std::thread;
+
+// What a shell command printed and how it exited
+pub struct ShellOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub code: Option<i32>, // None if killed by a signal
+}
+
+impl ShellOutput {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    // How the command exited, like 'exit 1'
+    pub fn exit(&self) -> String {
+        match self.code {
+            Some(code) => format!("exit {}", code),
+            None => "killed".to_string(),
+        }
+    }
+
+    // The lines of standard error on one line, for the message bar
+    pub fn errors(&self) -> String {
+        self.stderr
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<&str>>()
+            .join("; ")
+    }
+}
+
+/*
+ * Run a command with the shell, feeding it 'input' on standard input.
+ * Without input the command can't read from the terminal either. The
+ * input is written from another thread so that a command that prints
+ * while reading doesn't block on a full pipe.
+ */
+pub fn run_shell(command: &str, input: Option<&str>) -> Result<ShellOutput, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{}: {}", command, e))?;
+    let writer = match (child.stdin.take(), input) {
+        (Some(mut stdin), Some(input)) => {
+            let input = input.to_string();
+            // A command that exits without reading all of it closes the pipe, which is fine
+            Some(thread::spawn(move || {
+                let _ = stdin.write_all(input.as_bytes());
+            }))
+        }
+        _ => None,
+    };
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("{}: {}", command, e))?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    Ok(ShellOutput {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        code: output.status.code(),
+    })
+}
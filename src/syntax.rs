@@ -4,6 +4,14 @@ use std::fmt;
 pub type SyntaxFlags = u32;
 pub const NUMBERS: SyntaxFlags = 1 << 0;
 pub const STRINGS: SyntaxFlags = 1 << 1;
+pub const BACKTICKS: SyntaxFlags = 1 << 2; // `backtick` strings
+pub const IGNORE_CASE: SyntaxFlags = 1 << 3; // case insensitive keywords
+pub const KEYS: SyntaxFlags = 1 << 4; // keys of 'key: value' and 'key = value' pairs
+pub const SECTIONS: SyntaxFlags = 1 << 5; // section headers like '[section]'
+pub const TAGS: SyntaxFlags = 1 << 6; // markup tags like '<a href="...">'
+pub const MARKDOWN: SyntaxFlags = 1 << 7; // headings, emphasis, code spans and links
+pub const DIFF: SyntaxFlags = 1 << 8; // added and removed lines of diffs
+pub const BRACKETS: SyntaxFlags = 1 << 9; // brackets are expected to be balanced
 
 pub enum FileType {
     C,
@@ -11,6 +19,21 @@ pub enum FileType {
     Rust,
     Python,
     P2sh,
+    Dockerfile,
+    Go,
+    JavaScript,
+    TypeScript,
+    Json,
+    Yaml,
+    Toml,
+    Markdown,
+    Makefile,
+    Sql,
+    Lua,
+    Java,
+    Html,
+    Css,
+    Diff,
 }
 
 impl fmt::Display for FileType {
@@ -21,6 +44,42 @@ impl fmt::Display for FileType {
             FileType::Rust => write!(f, "RUST"),
             FileType::Python => write!(f, "Python"),
             FileType::P2sh => write!(f, "P2SH"),
+            FileType::Dockerfile => write!(f, "Dockerfile"),
+            FileType::Go => write!(f, "Go"),
+            FileType::JavaScript => write!(f, "JavaScript"),
+            FileType::TypeScript => write!(f, "TypeScript"),
+            FileType::Json => write!(f, "JSON"),
+            FileType::Yaml => write!(f, "YAML"),
+            FileType::Toml => write!(f, "TOML"),
+            FileType::Markdown => write!(f, "Markdown"),
+            FileType::Makefile => write!(f, "Makefile"),
+            FileType::Sql => write!(f, "SQL"),
+            FileType::Lua => write!(f, "Lua"),
+            FileType::Java => write!(f, "Java"),
+            FileType::Html => write!(f, "HTML"),
+            FileType::Css => write!(f, "CSS"),
+            FileType::Diff => write!(f, "Diff"),
+        }
+    }
+}
+
+/*
+ * Ways a file is matched to a syntax. Besides the file extension, a file
+ * can be matched by its name, or a glob pattern of the name, and by the
+ * interpreter on the shebang line of scripts.
+ */
+pub struct FileMatch {
+    pub extensions: Vec<String>,   // e.g. "rs"
+    pub patterns: Vec<String>,     // file names or glob patterns, e.g. "Makefile", "*.mk"
+    pub interpreters: Vec<String>, // shebang interpreters, e.g. "python"
+}
+
+impl FileMatch {
+    pub fn new(extensions: Vec<&str>, patterns: Vec<&str>, interpreters: Vec<&str>) -> Self {
+        Self {
+            extensions: extensions.iter().map(|s| s.to_string()).collect(),
+            patterns: patterns.iter().map(|s| s.to_string()).collect(),
+            interpreters: interpreters.iter().map(|s| s.to_string()).collect(),
         }
     }
 }
@@ -39,6 +98,210 @@ impl Comment {
     }
 }
 
+/*
+ * Rules for recognizing number literals. A literal starts with a digit (or a
+ * '.' followed by a digit) and may carry a radix prefix such as "0x", digit
+ * separators, an exponent and a type suffix. The whole literal has to end at
+ * a non identifier character, otherwise it is part of a word like "1abc".
+ */
+pub struct Number {
+    pub prefixes: Vec<(String, u32)>, // radix prefixes, e.g. ("0x", 16)
+    pub separator: Option<char>,      // digit separator, e.g. '_' in 1_000
+    pub exponent: bool,               // if exponents like 1e-9 are allowed
+    pub suffixes: Vec<String>,        // type suffixes, e.g. u32 or f
+}
+
+impl Number {
+    pub fn new(
+        prefixes: Vec<(&str, u32)>,
+        separator: Option<char>,
+        exponent: bool,
+        suffixes: Vec<&str>,
+    ) -> Self {
+        Self {
+            prefixes: prefixes.iter().map(|&(p, r)| (p.to_string(), r)).collect(),
+            separator,
+            exponent,
+            suffixes: suffixes.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    pub fn is_ident_char(ch: char) -> bool {
+        ch.is_alphanumeric() || ch == '_'
+    }
+
+    // Length of the digits of 'radix' starting at 'chars[i]', including separators
+    fn digits_len(&self, chars: &[char], i: usize, radix: u32) -> usize {
+        let mut j = i;
+        while j < chars.len() {
+            let c = chars[j];
+            if c.is_digit(radix) || (j > i && Some(c) == self.separator) {
+                j += 1;
+            } else {
+                break;
+            }
+        }
+        // A trailing separator is not part of the number
+        while j > i && Some(chars[j - 1]) == self.separator {
+            j -= 1;
+        }
+        j - i
+    }
+
+    fn starts_with(chars: &[char], i: usize, s: &str) -> bool {
+        i + s.len() <= chars.len()
+            && s.chars()
+                .zip(&chars[i..])
+                .all(|(p, c)| c.eq_ignore_ascii_case(&p))
+    }
+
+    /*
+     * Return the number of characters in the number literal at the start of
+     * 'chars', or 0 if 'chars' does not start with a valid number literal.
+     */
+    pub fn literal_len(&self, chars: &[char]) -> usize {
+        let mut i = 0;
+        let mut radix = 10;
+
+        for (prefix, r) in &self.prefixes {
+            if Self::starts_with(chars, 0, prefix) {
+                let len = self.digits_len(chars, prefix.len(), *r);
+                if len > 0 {
+                    i = prefix.len() + len;
+                    radix = *r;
+                    break;
+                }
+            }
+        }
+
+        if radix == 10 {
+            i = self.digits_len(chars, 0, 10);
+            // Fractional part, but not a range like 1..2 or a method call like 1.max()
+            if i < chars.len() && chars[i] == '.' {
+                match chars.get(i + 1) {
+                    Some(c) if c.is_ascii_digit() => {
+                        i += 1;
+                        i += self.digits_len(chars, i, 10);
+                    }
+                    Some(c) if *c == '.' || Self::is_ident_char(*c) => {}
+                    _ if i > 0 => i += 1,
+                    _ => {}
+                }
+            }
+            if i == 0 {
+                return 0;
+            }
+            if self.exponent && i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                let len = self.digits_len(chars, j, 10);
+                if len > 0 {
+                    i = j + len;
+                }
+            }
+        }
+
+        // Pick the longest suffix that matches
+        let suffix_len = self
+            .suffixes
+            .iter()
+            .filter(|s| Self::starts_with(chars, i, s))
+            .map(|s| s.len())
+            .max()
+            .unwrap_or(0);
+        i += suffix_len;
+
+        match chars.get(i) {
+            Some(c) if Self::is_ident_char(*c) => 0,
+            _ => i,
+        }
+    }
+}
+
+// Indent with a tab or a number of spaces per level
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IndentStyle {
+    Tabs,
+    Spaces(u16),
+}
+
+impl IndentStyle {
+    // Text of one indentation level
+    pub fn unit(&self) -> String {
+        match self {
+            IndentStyle::Tabs => "\t".to_string(),
+            IndentStyle::Spaces(width) => " ".repeat(*width as usize),
+        }
+    }
+}
+
+impl fmt::Display for IndentStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndentStyle::Tabs => write!(f, "tabs"),
+            IndentStyle::Spaces(width) => write!(f, "{} spaces", width),
+        }
+    }
+}
+
+/*
+ * Rules for indenting new lines. A line ending with one of 'open' (ignoring
+ * comments) starts a block, so the next line is indented one more level. A
+ * line that consists of just one of 'close' ends a block and is dedented.
+ */
+pub struct Indent {
+    pub style: IndentStyle, // default indentation of the language
+    pub open: Vec<String>,
+    pub close: Vec<String>,
+}
+
+impl Indent {
+    pub fn new(style: IndentStyle, open: Vec<&str>, close: Vec<&str>) -> Self {
+        Self {
+            style,
+            open: open.iter().map(|s| s.to_string()).collect(),
+            close: close.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    // Check if 'code' ends with a block opener. Words like 'do' must not end a longer word.
+    pub fn opens_block(&self, code: &str) -> bool {
+        self.open.iter().any(|open| {
+            code.strip_suffix(open.as_str()).is_some_and(|before| {
+                !open.starts_with(Number::is_ident_char) || !before.ends_with(Number::is_ident_char)
+            })
+        })
+    }
+
+    /*
+     * Check if typing 'ch' between 'before' and 'after' on a line completes
+     * a closer that is all of the line. A bracket is complete when its last
+     * character is typed, a word like 'fi' only when something that can't
+     * continue it follows, so that 'file' is left alone.
+     */
+    pub fn completes_closer(&self, before: &str, ch: char, after: &str) -> bool {
+        if !after.trim().is_empty() {
+            return false;
+        }
+        self.close.iter().any(|close| {
+            if close.starts_with(Number::is_ident_char) {
+                !Number::is_ident_char(ch) && before.trim() == close
+            } else {
+                close.ends_with(ch) && format!("{}{}", before, ch).trim() == close
+            }
+        })
+    }
+
+    // Check if 'text' starts with a closing bracket, as when Enter is pressed within '{}'
+    pub fn starts_with_closer(&self, text: &str) -> bool {
+        self.close.iter().any(|close| {
+            !close.starts_with(Number::is_ident_char) && text.starts_with(close.as_str())
+        })
+    }
+}
+
 pub enum Keyword {
     Base(String),
     Type(String),
@@ -48,26 +311,36 @@ pub enum Keyword {
 
 pub struct Syntax {
     pub filetype: FileType,
-    pub filematch: Vec<String>,
+    pub filematch: FileMatch,
     pub flags: SyntaxFlags,
     pub comment: Comment,
+    pub indent: Indent,
+    pub number: Number,
     pub keywords: Vec<Keyword>,
+    pub formatter: Option<&'static str>, // command formatting standard input to standard output
 }
 
 impl Syntax {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         filetype: FileType,
-        filematch: Vec<&str>,
+        filematch: FileMatch,
         flags: SyntaxFlags,
         comment: Comment,
+        indent: Indent,
+        number: Number,
         keywords: Vec<Keyword>,
+        formatter: Option<&'static str>,
     ) -> Self {
         Self {
             filetype,
-            filematch: filematch.iter().map(|s| s.to_string()).collect(),
+            filematch,
             flags,
             comment,
+            indent,
+            number,
             keywords,
+            formatter,
         }
     }
 }
@@ -77,9 +350,20 @@ lazy_static! {
     pub static ref HLDB: Vec<Syntax> = vec![
         Syntax::new(
             FileType::C,
-            vec!["c", "h", "cc", "cpp", "hpp"],
-            NUMBERS | STRINGS,
+            FileMatch::new(
+                vec!["c", "h", "cc", "cpp", "hpp", "cxx", "hxx"],
+                vec!["*.h.in"],
+                vec!["tcc"],
+            ),
+            NUMBERS | STRINGS | BRACKETS,
             Comment::new(vec!["//"], Some(("/*", "*/"))),
+            Indent::new(IndentStyle::Spaces(4), vec!["{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(
+                vec![("0x", 16), ("0b", 2)],
+                None,
+                true,
+                vec!["ull", "llu", "ul", "lu", "ll", "u", "l", "f"],
+            ),
             vec![
                 Keyword::Base("switch".into()),
                 Keyword::Base("if".into()),
@@ -130,12 +414,25 @@ lazy_static! {
                 Keyword::Type("float".into()),
                 Keyword::Type("double".into()),
             ],
+            Some("clang-format"),
         ),
         Syntax::new(
             FileType::Sh,
-            vec!["sh"],
+            FileMatch::new(
+                vec!["sh", "bash", "zsh", "ksh"],
+                vec![
+                    ".bashrc", ".bash_profile", ".bash_logout", ".bash_aliases", ".profile",
+                    ".zshrc", ".zprofile", ".zshenv", ".kshrc", "PKGBUILD", "*.ebuild",
+                ],
+                vec!["sh", "bash", "zsh", "ksh", "dash", "ash"],
+            ),
             NUMBERS | STRINGS,
             Comment::new(vec!["#"], None),
+            Indent::new(IndentStyle::Spaces(4),
+                vec!["then", "do", "else", "{", "("],
+                vec!["fi", "done", "else", "esac", "}", ")"],
+            ),
+            Number::new(vec![("0x", 16)], None, false, vec![]),
             vec![
                 Keyword::Base("if".into()),
                 Keyword::Base("then".into()),
@@ -275,12 +572,23 @@ lazy_static! {
                 Keyword::Type(".".into()),
                 Keyword::Type("=".into()),
             ],
+            Some("shfmt"),
         ),
         Syntax::new(
             FileType::Rust,
-            vec!["rs"],
-            NUMBERS | STRINGS,
+            FileMatch::new(vec!["rs"], vec![], vec!["rust-script", "run-cargo-script"]),
+            NUMBERS | STRINGS | BRACKETS,
             Comment::new(vec!["//"], Some(("/*", "*/"))),
+            Indent::new(IndentStyle::Spaces(4), vec!["{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(
+                vec![("0x", 16), ("0o", 8), ("0b", 2)],
+                Some('_'),
+                true,
+                vec![
+                    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64",
+                    "u128", "usize", "f32", "f64",
+                ],
+            ),
             vec![
                 Keyword::Base("as".into()),
                 Keyword::Base("async".into()),
@@ -335,13 +643,25 @@ lazy_static! {
                 Keyword::Type("usize".into()),
                 Keyword::Type("isize".into()),
             ],
+            Some("rustfmt --edition 2021"),
         ),
         Syntax::new(
             FileType::Python,
-            vec!["py"],
-            NUMBERS | STRINGS,
+            FileMatch::new(
+                vec!["py", "pyw", "pyi"],
+                vec!["SConstruct", "SConscript", ".pythonrc"],
+                vec!["python", "pypy"],
+            ),
+            NUMBERS | STRINGS | BRACKETS,
             // Although, """ is not a comment, it mimics one
             Comment::new(vec!["#"], Some(("\"\"\"", "\"\"\""))),
+            Indent::new(IndentStyle::Spaces(4), vec![":", "{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(
+                vec![("0x", 16), ("0o", 8), ("0b", 2)],
+                Some('_'),
+                true,
+                vec!["j"],
+            ),
             vec![
                 Keyword::Base("False".into()),
                 Keyword::Base("None".into()),
@@ -392,12 +712,15 @@ lazy_static! {
                 Keyword::Type("set".into()),
                 Keyword::Type("frozenset".into()),
             ],
+            Some("black --quiet -"),
         ),
         Syntax::new(
             FileType::P2sh,
-            vec!["p2", "p2sh"],
-            NUMBERS | STRINGS,
+            FileMatch::new(vec!["p2", "p2sh"], vec![], vec!["p2sh"]),
+            NUMBERS | STRINGS | BRACKETS,
             Comment::new(vec!["#", "//"], None),
+            Indent::new(IndentStyle::Spaces(4), vec!["{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(vec![("0x", 16), ("0o", 8), ("0b", 2)], None, true, vec![]),
             vec![
                 Keyword::Base("_".into()),
                 Keyword::Base("let".into()),
@@ -468,6 +791,802 @@ lazy_static! {
                 Keyword::BuiltinFn("pcap_read_all".into()),
                 Keyword::BuiltinFn("pcap_write".into()),
             ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Dockerfile,
+            FileMatch::new(
+                vec!["dockerfile"],
+                vec!["Dockerfile", "Dockerfile.*", "Containerfile", "Containerfile.*"],
+                vec![],
+            ),
+            NUMBERS | STRINGS,
+            Comment::new(vec!["#"], None),
+            Indent::new(IndentStyle::Spaces(4), vec![], vec![]),
+            Number::new(vec![], None, false, vec![]),
+            vec![
+                Keyword::Base("FROM".into()),
+                Keyword::Base("AS".into()),
+                Keyword::Base("RUN".into()),
+                Keyword::Base("CMD".into()),
+                Keyword::Base("LABEL".into()),
+                Keyword::Base("MAINTAINER".into()),
+                Keyword::Base("EXPOSE".into()),
+                Keyword::Base("ENV".into()),
+                Keyword::Base("ADD".into()),
+                Keyword::Base("COPY".into()),
+                Keyword::Base("ENTRYPOINT".into()),
+                Keyword::Base("VOLUME".into()),
+                Keyword::Base("USER".into()),
+                Keyword::Base("WORKDIR".into()),
+                Keyword::Base("ARG".into()),
+                Keyword::Base("ONBUILD".into()),
+                Keyword::Base("STOPSIGNAL".into()),
+                Keyword::Base("HEALTHCHECK".into()),
+                Keyword::Base("SHELL".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Go,
+            FileMatch::new(vec!["go"], vec![], vec![]),
+            NUMBERS | STRINGS | BACKTICKS | BRACKETS,
+            Comment::new(vec!["//"], Some(("/*", "*/"))),
+            Indent::new(IndentStyle::Tabs, vec!["{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(vec![("0x", 16), ("0o", 8), ("0b", 2)], Some('_'), true, vec!["i"]),
+            vec![
+                Keyword::Base("break".into()),
+                Keyword::Base("case".into()),
+                Keyword::Base("chan".into()),
+                Keyword::Base("const".into()),
+                Keyword::Base("continue".into()),
+                Keyword::Base("default".into()),
+                Keyword::Base("defer".into()),
+                Keyword::Base("else".into()),
+                Keyword::Base("fallthrough".into()),
+                Keyword::Base("for".into()),
+                Keyword::Base("func".into()),
+                Keyword::Base("go".into()),
+                Keyword::Base("goto".into()),
+                Keyword::Base("if".into()),
+                Keyword::Base("import".into()),
+                Keyword::Base("interface".into()),
+                Keyword::Base("map".into()),
+                Keyword::Base("package".into()),
+                Keyword::Base("range".into()),
+                Keyword::Base("return".into()),
+                Keyword::Base("select".into()),
+                Keyword::Base("struct".into()),
+                Keyword::Base("switch".into()),
+                Keyword::Base("type".into()),
+                Keyword::Base("var".into()),
+                Keyword::Type("bool".into()),
+                Keyword::Type("byte".into()),
+                Keyword::Type("complex64".into()),
+                Keyword::Type("complex128".into()),
+                Keyword::Type("error".into()),
+                Keyword::Type("float32".into()),
+                Keyword::Type("float64".into()),
+                Keyword::Type("int".into()),
+                Keyword::Type("int8".into()),
+                Keyword::Type("int16".into()),
+                Keyword::Type("int32".into()),
+                Keyword::Type("int64".into()),
+                Keyword::Type("rune".into()),
+                Keyword::Type("string".into()),
+                Keyword::Type("uint".into()),
+                Keyword::Type("uint8".into()),
+                Keyword::Type("uint16".into()),
+                Keyword::Type("uint32".into()),
+                Keyword::Type("uint64".into()),
+                Keyword::Type("uintptr".into()),
+                Keyword::Type("any".into()),
+                Keyword::Type("comparable".into()),
+                Keyword::BuiltinFn("append".into()),
+                Keyword::BuiltinFn("cap".into()),
+                Keyword::BuiltinFn("clear".into()),
+                Keyword::BuiltinFn("close".into()),
+                Keyword::BuiltinFn("complex".into()),
+                Keyword::BuiltinFn("copy".into()),
+                Keyword::BuiltinFn("delete".into()),
+                Keyword::BuiltinFn("imag".into()),
+                Keyword::BuiltinFn("len".into()),
+                Keyword::BuiltinFn("make".into()),
+                Keyword::BuiltinFn("max".into()),
+                Keyword::BuiltinFn("min".into()),
+                Keyword::BuiltinFn("new".into()),
+                Keyword::BuiltinFn("panic".into()),
+                Keyword::BuiltinFn("print".into()),
+                Keyword::BuiltinFn("println".into()),
+                Keyword::BuiltinFn("real".into()),
+                Keyword::BuiltinFn("recover".into()),
+                Keyword::BuiltinVar("true".into()),
+                Keyword::BuiltinVar("false".into()),
+                Keyword::BuiltinVar("iota".into()),
+                Keyword::BuiltinVar("nil".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::JavaScript,
+            FileMatch::new(vec!["js", "mjs", "cjs", "jsx"], vec![], vec!["node", "nodejs"]),
+            NUMBERS | STRINGS | BACKTICKS | BRACKETS,
+            Comment::new(vec!["//"], Some(("/*", "*/"))),
+            Indent::new(IndentStyle::Spaces(2), vec!["{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(vec![("0x", 16), ("0o", 8), ("0b", 2)], Some('_'), true, vec!["n"]),
+            vec![
+                Keyword::Base("break".into()),
+                Keyword::Base("case".into()),
+                Keyword::Base("catch".into()),
+                Keyword::Base("class".into()),
+                Keyword::Base("const".into()),
+                Keyword::Base("continue".into()),
+                Keyword::Base("debugger".into()),
+                Keyword::Base("default".into()),
+                Keyword::Base("delete".into()),
+                Keyword::Base("do".into()),
+                Keyword::Base("else".into()),
+                Keyword::Base("export".into()),
+                Keyword::Base("extends".into()),
+                Keyword::Base("finally".into()),
+                Keyword::Base("for".into()),
+                Keyword::Base("function".into()),
+                Keyword::Base("if".into()),
+                Keyword::Base("import".into()),
+                Keyword::Base("in".into()),
+                Keyword::Base("instanceof".into()),
+                Keyword::Base("let".into()),
+                Keyword::Base("new".into()),
+                Keyword::Base("return".into()),
+                Keyword::Base("super".into()),
+                Keyword::Base("switch".into()),
+                Keyword::Base("this".into()),
+                Keyword::Base("throw".into()),
+                Keyword::Base("try".into()),
+                Keyword::Base("typeof".into()),
+                Keyword::Base("var".into()),
+                Keyword::Base("void".into()),
+                Keyword::Base("while".into()),
+                Keyword::Base("with".into()),
+                Keyword::Base("yield".into()),
+                Keyword::Base("async".into()),
+                Keyword::Base("await".into()),
+                Keyword::Base("of".into()),
+                Keyword::Base("static".into()),
+                Keyword::Base("get".into()),
+                Keyword::Base("set".into()),
+                Keyword::Base("from".into()),
+                Keyword::Type("Object".into()),
+                Keyword::Type("Array".into()),
+                Keyword::Type("String".into()),
+                Keyword::Type("Number".into()),
+                Keyword::Type("Boolean".into()),
+                Keyword::Type("Promise".into()),
+                Keyword::Type("Map".into()),
+                Keyword::Type("Set".into()),
+                Keyword::Type("WeakMap".into()),
+                Keyword::Type("WeakSet".into()),
+                Keyword::Type("Symbol".into()),
+                Keyword::Type("Date".into()),
+                Keyword::Type("RegExp".into()),
+                Keyword::Type("Error".into()),
+                Keyword::Type("JSON".into()),
+                Keyword::Type("Math".into()),
+                Keyword::Type("BigInt".into()),
+                Keyword::BuiltinFn("parseInt".into()),
+                Keyword::BuiltinFn("parseFloat".into()),
+                Keyword::BuiltinFn("isNaN".into()),
+                Keyword::BuiltinFn("isFinite".into()),
+                Keyword::BuiltinFn("require".into()),
+                Keyword::BuiltinFn("setTimeout".into()),
+                Keyword::BuiltinFn("setInterval".into()),
+                Keyword::BuiltinFn("clearTimeout".into()),
+                Keyword::BuiltinFn("clearInterval".into()),
+                Keyword::BuiltinVar("true".into()),
+                Keyword::BuiltinVar("false".into()),
+                Keyword::BuiltinVar("null".into()),
+                Keyword::BuiltinVar("undefined".into()),
+                Keyword::BuiltinVar("NaN".into()),
+                Keyword::BuiltinVar("Infinity".into()),
+                Keyword::BuiltinVar("console".into()),
+                Keyword::BuiltinVar("window".into()),
+                Keyword::BuiltinVar("document".into()),
+                Keyword::BuiltinVar("globalThis".into()),
+                Keyword::BuiltinVar("module".into()),
+                Keyword::BuiltinVar("exports".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::TypeScript,
+            FileMatch::new(vec!["ts", "mts", "cts", "tsx"], vec![], vec!["deno", "ts-node"]),
+            NUMBERS | STRINGS | BACKTICKS | BRACKETS,
+            Comment::new(vec!["//"], Some(("/*", "*/"))),
+            Indent::new(IndentStyle::Spaces(2), vec!["{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(vec![("0x", 16), ("0o", 8), ("0b", 2)], Some('_'), true, vec!["n"]),
+            vec![
+                Keyword::Base("break".into()),
+                Keyword::Base("case".into()),
+                Keyword::Base("catch".into()),
+                Keyword::Base("class".into()),
+                Keyword::Base("const".into()),
+                Keyword::Base("continue".into()),
+                Keyword::Base("debugger".into()),
+                Keyword::Base("default".into()),
+                Keyword::Base("delete".into()),
+                Keyword::Base("do".into()),
+                Keyword::Base("else".into()),
+                Keyword::Base("export".into()),
+                Keyword::Base("extends".into()),
+                Keyword::Base("finally".into()),
+                Keyword::Base("for".into()),
+                Keyword::Base("function".into()),
+                Keyword::Base("if".into()),
+                Keyword::Base("import".into()),
+                Keyword::Base("in".into()),
+                Keyword::Base("instanceof".into()),
+                Keyword::Base("let".into()),
+                Keyword::Base("new".into()),
+                Keyword::Base("return".into()),
+                Keyword::Base("super".into()),
+                Keyword::Base("switch".into()),
+                Keyword::Base("this".into()),
+                Keyword::Base("throw".into()),
+                Keyword::Base("try".into()),
+                Keyword::Base("typeof".into()),
+                Keyword::Base("var".into()),
+                Keyword::Base("void".into()),
+                Keyword::Base("while".into()),
+                Keyword::Base("with".into()),
+                Keyword::Base("yield".into()),
+                Keyword::Base("async".into()),
+                Keyword::Base("await".into()),
+                Keyword::Base("of".into()),
+                Keyword::Base("static".into()),
+                Keyword::Base("get".into()),
+                Keyword::Base("set".into()),
+                Keyword::Base("from".into()),
+                Keyword::Base("interface".into()),
+                Keyword::Base("type".into()),
+                Keyword::Base("enum".into()),
+                Keyword::Base("namespace".into()),
+                Keyword::Base("declare".into()),
+                Keyword::Base("abstract".into()),
+                Keyword::Base("implements".into()),
+                Keyword::Base("private".into()),
+                Keyword::Base("protected".into()),
+                Keyword::Base("public".into()),
+                Keyword::Base("readonly".into()),
+                Keyword::Base("keyof".into()),
+                Keyword::Base("infer".into()),
+                Keyword::Base("as".into()),
+                Keyword::Base("is".into()),
+                Keyword::Base("satisfies".into()),
+                Keyword::Type("Object".into()),
+                Keyword::Type("Array".into()),
+                Keyword::Type("String".into()),
+                Keyword::Type("Number".into()),
+                Keyword::Type("Boolean".into()),
+                Keyword::Type("Promise".into()),
+                Keyword::Type("Map".into()),
+                Keyword::Type("Set".into()),
+                Keyword::Type("WeakMap".into()),
+                Keyword::Type("WeakSet".into()),
+                Keyword::Type("Symbol".into()),
+                Keyword::Type("Date".into()),
+                Keyword::Type("RegExp".into()),
+                Keyword::Type("Error".into()),
+                Keyword::Type("JSON".into()),
+                Keyword::Type("Math".into()),
+                Keyword::Type("BigInt".into()),
+                Keyword::Type("any".into()),
+                Keyword::Type("unknown".into()),
+                Keyword::Type("never".into()),
+                Keyword::Type("void".into()),
+                Keyword::Type("string".into()),
+                Keyword::Type("number".into()),
+                Keyword::Type("boolean".into()),
+                Keyword::Type("bigint".into()),
+                Keyword::Type("symbol".into()),
+                Keyword::Type("object".into()),
+                Keyword::Type("Record".into()),
+                Keyword::Type("Partial".into()),
+                Keyword::Type("Readonly".into()),
+                Keyword::BuiltinFn("parseInt".into()),
+                Keyword::BuiltinFn("parseFloat".into()),
+                Keyword::BuiltinFn("isNaN".into()),
+                Keyword::BuiltinFn("isFinite".into()),
+                Keyword::BuiltinFn("require".into()),
+                Keyword::BuiltinFn("setTimeout".into()),
+                Keyword::BuiltinFn("setInterval".into()),
+                Keyword::BuiltinFn("clearTimeout".into()),
+                Keyword::BuiltinFn("clearInterval".into()),
+                Keyword::BuiltinVar("true".into()),
+                Keyword::BuiltinVar("false".into()),
+                Keyword::BuiltinVar("null".into()),
+                Keyword::BuiltinVar("undefined".into()),
+                Keyword::BuiltinVar("NaN".into()),
+                Keyword::BuiltinVar("Infinity".into()),
+                Keyword::BuiltinVar("console".into()),
+                Keyword::BuiltinVar("window".into()),
+                Keyword::BuiltinVar("document".into()),
+                Keyword::BuiltinVar("globalThis".into()),
+                Keyword::BuiltinVar("module".into()),
+                Keyword::BuiltinVar("exports".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Json,
+            FileMatch::new(
+                vec!["json", "jsonc", "json5", "geojson"],
+                vec![".babelrc", ".eslintrc", ".prettierrc", "composer.lock", "*.code-workspace"],
+                vec![],
+            ),
+            NUMBERS | STRINGS | KEYS | BRACKETS,
+            Comment::new(vec!["//"], Some(("/*", "*/"))),
+            Indent::new(IndentStyle::Spaces(2), vec!["{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(vec![], None, true, vec![]),
+            vec![
+                Keyword::BuiltinVar("true".into()),
+                Keyword::BuiltinVar("false".into()),
+                Keyword::BuiltinVar("null".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Yaml,
+            FileMatch::new(vec!["yaml", "yml"], vec![".clang-format", ".clang-tidy"], vec![]),
+            NUMBERS | STRINGS | KEYS,
+            Comment::new(vec!["#"], None),
+            Indent::new(IndentStyle::Spaces(2), vec![":"], vec![]),
+            Number::new(vec![("0x", 16), ("0o", 8)], Some('_'), true, vec![]),
+            vec![
+                Keyword::BuiltinVar("true".into()),
+                Keyword::BuiltinVar("false".into()),
+                Keyword::BuiltinVar("True".into()),
+                Keyword::BuiltinVar("False".into()),
+                Keyword::BuiltinVar("TRUE".into()),
+                Keyword::BuiltinVar("FALSE".into()),
+                Keyword::BuiltinVar("yes".into()),
+                Keyword::BuiltinVar("no".into()),
+                Keyword::BuiltinVar("on".into()),
+                Keyword::BuiltinVar("off".into()),
+                Keyword::BuiltinVar("null".into()),
+                Keyword::BuiltinVar("Null".into()),
+                Keyword::BuiltinVar("NULL".into()),
+                Keyword::BuiltinVar("~".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Toml,
+            FileMatch::new(vec!["toml"], vec!["Cargo.lock", "Pipfile", "poetry.lock"], vec![]),
+            NUMBERS | STRINGS | KEYS | SECTIONS | BRACKETS,
+            Comment::new(vec!["#"], None),
+            Indent::new(IndentStyle::Spaces(4), vec!["[", "{"], vec!["]", "}"]),
+            Number::new(vec![("0x", 16), ("0o", 8), ("0b", 2)], Some('_'), true, vec![]),
+            vec![
+                Keyword::BuiltinVar("true".into()),
+                Keyword::BuiltinVar("false".into()),
+                Keyword::BuiltinVar("inf".into()),
+                Keyword::BuiltinVar("nan".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Markdown,
+            FileMatch::new(vec!["md", "markdown", "mkd", "mdown"], vec![], vec![]),
+            MARKDOWN,
+            Comment::new(vec![], Some(("```", "```"))),
+            Indent::new(IndentStyle::Spaces(4), vec![], vec![]),
+            Number::new(vec![], None, false, vec![]),
+            vec![],
+            None,
+        ),
+        Syntax::new(
+            FileType::Makefile,
+            FileMatch::new(
+                vec!["mk", "mak", "make"],
+                vec!["Makefile", "makefile", "GNUmakefile", "Makefile.*", "*.mk.in"],
+                vec!["make"],
+            ),
+            STRINGS | KEYS,
+            Comment::new(vec!["#"], None),
+            Indent::new(IndentStyle::Tabs, vec![], vec![]),
+            Number::new(vec![], None, false, vec![]),
+            vec![
+                Keyword::Base("ifeq".into()),
+                Keyword::Base("ifneq".into()),
+                Keyword::Base("ifdef".into()),
+                Keyword::Base("ifndef".into()),
+                Keyword::Base("else".into()),
+                Keyword::Base("endif".into()),
+                Keyword::Base("include".into()),
+                Keyword::Base("-include".into()),
+                Keyword::Base("sinclude".into()),
+                Keyword::Base("define".into()),
+                Keyword::Base("endef".into()),
+                Keyword::Base("export".into()),
+                Keyword::Base("unexport".into()),
+                Keyword::Base("override".into()),
+                Keyword::Base("private".into()),
+                Keyword::Base("vpath".into()),
+                Keyword::BuiltinFn("wildcard".into()),
+                Keyword::BuiltinFn("patsubst".into()),
+                Keyword::BuiltinFn("subst".into()),
+                Keyword::BuiltinFn("shell".into()),
+                Keyword::BuiltinFn("foreach".into()),
+                Keyword::BuiltinFn("call".into()),
+                Keyword::BuiltinFn("eval".into()),
+                Keyword::BuiltinFn("filter".into()),
+                Keyword::BuiltinFn("filter-out".into()),
+                Keyword::BuiltinFn("sort".into()),
+                Keyword::BuiltinFn("dir".into()),
+                Keyword::BuiltinFn("notdir".into()),
+                Keyword::BuiltinFn("basename".into()),
+                Keyword::BuiltinFn("suffix".into()),
+                Keyword::BuiltinFn("addprefix".into()),
+                Keyword::BuiltinFn("addsuffix".into()),
+                Keyword::BuiltinFn("join".into()),
+                Keyword::BuiltinFn("word".into()),
+                Keyword::BuiltinFn("words".into()),
+                Keyword::BuiltinFn("wordlist".into()),
+                Keyword::BuiltinFn("firstword".into()),
+                Keyword::BuiltinFn("lastword".into()),
+                Keyword::BuiltinFn("strip".into()),
+                Keyword::BuiltinFn("findstring".into()),
+                Keyword::BuiltinFn("error".into()),
+                Keyword::BuiltinFn("warning".into()),
+                Keyword::BuiltinFn("info".into()),
+                Keyword::BuiltinFn("origin".into()),
+                Keyword::BuiltinFn("flavor".into()),
+                Keyword::BuiltinFn("value".into()),
+                Keyword::BuiltinFn("abspath".into()),
+                Keyword::BuiltinFn("realpath".into()),
+                Keyword::BuiltinFn("if".into()),
+                Keyword::BuiltinFn("or".into()),
+                Keyword::BuiltinFn("and".into()),
+                Keyword::BuiltinFn("file".into()),
+                Keyword::BuiltinVar(".PHONY".into()),
+                Keyword::BuiltinVar(".DEFAULT".into()),
+                Keyword::BuiltinVar(".SUFFIXES".into()),
+                Keyword::BuiltinVar(".PRECIOUS".into()),
+                Keyword::BuiltinVar(".SILENT".into()),
+                Keyword::BuiltinVar(".ONESHELL".into()),
+                Keyword::BuiltinVar("MAKE".into()),
+                Keyword::BuiltinVar("MAKEFLAGS".into()),
+                Keyword::BuiltinVar("CURDIR".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Sql,
+            FileMatch::new(vec!["sql", "psql", "mysql"], vec![], vec![]),
+            NUMBERS | STRINGS | IGNORE_CASE | BRACKETS,
+            Comment::new(vec!["--"], Some(("/*", "*/"))),
+            Indent::new(IndentStyle::Spaces(4), vec!["("], vec![")"]),
+            Number::new(vec![("0x", 16)], None, true, vec![]),
+            vec![
+                Keyword::Base("SELECT".into()),
+                Keyword::Base("FROM".into()),
+                Keyword::Base("WHERE".into()),
+                Keyword::Base("INSERT".into()),
+                Keyword::Base("INTO".into()),
+                Keyword::Base("VALUES".into()),
+                Keyword::Base("UPDATE".into()),
+                Keyword::Base("SET".into()),
+                Keyword::Base("DELETE".into()),
+                Keyword::Base("CREATE".into()),
+                Keyword::Base("TABLE".into()),
+                Keyword::Base("DROP".into()),
+                Keyword::Base("ALTER".into()),
+                Keyword::Base("ADD".into()),
+                Keyword::Base("COLUMN".into()),
+                Keyword::Base("INDEX".into()),
+                Keyword::Base("VIEW".into()),
+                Keyword::Base("TRIGGER".into()),
+                Keyword::Base("JOIN".into()),
+                Keyword::Base("LEFT".into()),
+                Keyword::Base("RIGHT".into()),
+                Keyword::Base("INNER".into()),
+                Keyword::Base("OUTER".into()),
+                Keyword::Base("FULL".into()),
+                Keyword::Base("CROSS".into()),
+                Keyword::Base("ON".into()),
+                Keyword::Base("USING".into()),
+                Keyword::Base("AS".into()),
+                Keyword::Base("AND".into()),
+                Keyword::Base("OR".into()),
+                Keyword::Base("NOT".into()),
+                Keyword::Base("NULL".into()),
+                Keyword::Base("IS".into()),
+                Keyword::Base("IN".into()),
+                Keyword::Base("LIKE".into()),
+                Keyword::Base("BETWEEN".into()),
+                Keyword::Base("ORDER".into()),
+                Keyword::Base("BY".into()),
+                Keyword::Base("GROUP".into()),
+                Keyword::Base("HAVING".into()),
+                Keyword::Base("LIMIT".into()),
+                Keyword::Base("OFFSET".into()),
+                Keyword::Base("UNION".into()),
+                Keyword::Base("ALL".into()),
+                Keyword::Base("DISTINCT".into()),
+                Keyword::Base("PRIMARY".into()),
+                Keyword::Base("KEY".into()),
+                Keyword::Base("FOREIGN".into()),
+                Keyword::Base("REFERENCES".into()),
+                Keyword::Base("UNIQUE".into()),
+                Keyword::Base("CHECK".into()),
+                Keyword::Base("DEFAULT".into()),
+                Keyword::Base("CONSTRAINT".into()),
+                Keyword::Base("CASE".into()),
+                Keyword::Base("WHEN".into()),
+                Keyword::Base("THEN".into()),
+                Keyword::Base("ELSE".into()),
+                Keyword::Base("END".into()),
+                Keyword::Base("BEGIN".into()),
+                Keyword::Base("COMMIT".into()),
+                Keyword::Base("ROLLBACK".into()),
+                Keyword::Base("TRANSACTION".into()),
+                Keyword::Base("EXISTS".into()),
+                Keyword::Base("WITH".into()),
+                Keyword::Base("RETURNING".into()),
+                Keyword::Base("ASC".into()),
+                Keyword::Base("DESC".into()),
+                Keyword::Base("IF".into()),
+                Keyword::Base("REPLACE".into()),
+                Keyword::Type("INT".into()),
+                Keyword::Type("INTEGER".into()),
+                Keyword::Type("BIGINT".into()),
+                Keyword::Type("SMALLINT".into()),
+                Keyword::Type("TINYINT".into()),
+                Keyword::Type("TEXT".into()),
+                Keyword::Type("VARCHAR".into()),
+                Keyword::Type("CHAR".into()),
+                Keyword::Type("BOOLEAN".into()),
+                Keyword::Type("BOOL".into()),
+                Keyword::Type("DATE".into()),
+                Keyword::Type("TIME".into()),
+                Keyword::Type("TIMESTAMP".into()),
+                Keyword::Type("INTERVAL".into()),
+                Keyword::Type("REAL".into()),
+                Keyword::Type("FLOAT".into()),
+                Keyword::Type("DOUBLE".into()),
+                Keyword::Type("DECIMAL".into()),
+                Keyword::Type("NUMERIC".into()),
+                Keyword::Type("BLOB".into()),
+                Keyword::Type("BYTEA".into()),
+                Keyword::Type("SERIAL".into()),
+                Keyword::Type("UUID".into()),
+                Keyword::Type("JSON".into()),
+                Keyword::Type("JSONB".into()),
+                Keyword::BuiltinFn("COUNT".into()),
+                Keyword::BuiltinFn("SUM".into()),
+                Keyword::BuiltinFn("AVG".into()),
+                Keyword::BuiltinFn("MIN".into()),
+                Keyword::BuiltinFn("MAX".into()),
+                Keyword::BuiltinFn("COALESCE".into()),
+                Keyword::BuiltinFn("NULLIF".into()),
+                Keyword::BuiltinFn("CAST".into()),
+                Keyword::BuiltinFn("NOW".into()),
+                Keyword::BuiltinFn("UPPER".into()),
+                Keyword::BuiltinFn("LOWER".into()),
+                Keyword::BuiltinFn("LENGTH".into()),
+                Keyword::BuiltinFn("SUBSTR".into()),
+                Keyword::BuiltinFn("SUBSTRING".into()),
+                Keyword::BuiltinFn("TRIM".into()),
+                Keyword::BuiltinFn("ROUND".into()),
+                Keyword::BuiltinFn("ABS".into()),
+                Keyword::BuiltinVar("TRUE".into()),
+                Keyword::BuiltinVar("FALSE".into()),
+                Keyword::BuiltinVar("CURRENT_DATE".into()),
+                Keyword::BuiltinVar("CURRENT_TIME".into()),
+                Keyword::BuiltinVar("CURRENT_TIMESTAMP".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Lua,
+            FileMatch::new(vec!["lua"], vec![".luacheckrc"], vec!["lua", "luajit"]),
+            NUMBERS | STRINGS | BRACKETS,
+            Comment::new(vec!["--"], Some(("--[[", "]]"))),
+            Indent::new(IndentStyle::Spaces(2),
+                vec!["then", "do", "else", "repeat", "{", "("],
+                vec!["end", "else", "}", ")"],
+            ),
+            Number::new(vec![("0x", 16)], None, true, vec![]),
+            vec![
+                Keyword::Base("and".into()),
+                Keyword::Base("break".into()),
+                Keyword::Base("do".into()),
+                Keyword::Base("else".into()),
+                Keyword::Base("elseif".into()),
+                Keyword::Base("end".into()),
+                Keyword::Base("for".into()),
+                Keyword::Base("function".into()),
+                Keyword::Base("goto".into()),
+                Keyword::Base("if".into()),
+                Keyword::Base("in".into()),
+                Keyword::Base("local".into()),
+                Keyword::Base("not".into()),
+                Keyword::Base("or".into()),
+                Keyword::Base("repeat".into()),
+                Keyword::Base("return".into()),
+                Keyword::Base("then".into()),
+                Keyword::Base("until".into()),
+                Keyword::Base("while".into()),
+                Keyword::BuiltinFn("print".into()),
+                Keyword::BuiltinFn("pairs".into()),
+                Keyword::BuiltinFn("ipairs".into()),
+                Keyword::BuiltinFn("type".into()),
+                Keyword::BuiltinFn("tostring".into()),
+                Keyword::BuiltinFn("tonumber".into()),
+                Keyword::BuiltinFn("require".into()),
+                Keyword::BuiltinFn("setmetatable".into()),
+                Keyword::BuiltinFn("getmetatable".into()),
+                Keyword::BuiltinFn("error".into()),
+                Keyword::BuiltinFn("assert".into()),
+                Keyword::BuiltinFn("pcall".into()),
+                Keyword::BuiltinFn("xpcall".into()),
+                Keyword::BuiltinFn("select".into()),
+                Keyword::BuiltinFn("next".into()),
+                Keyword::BuiltinFn("rawget".into()),
+                Keyword::BuiltinFn("rawset".into()),
+                Keyword::BuiltinFn("rawequal".into()),
+                Keyword::BuiltinFn("unpack".into()),
+                Keyword::BuiltinFn("load".into()),
+                Keyword::BuiltinFn("dofile".into()),
+                Keyword::BuiltinVar("true".into()),
+                Keyword::BuiltinVar("false".into()),
+                Keyword::BuiltinVar("nil".into()),
+                Keyword::BuiltinVar("self".into()),
+                Keyword::BuiltinVar("_G".into()),
+                Keyword::BuiltinVar("_VERSION".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Java,
+            FileMatch::new(vec!["java"], vec![], vec![]),
+            NUMBERS | STRINGS | BRACKETS,
+            Comment::new(vec!["//"], Some(("/*", "*/"))),
+            Indent::new(IndentStyle::Spaces(4), vec!["{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(
+                vec![("0x", 16), ("0b", 2)],
+                Some('_'),
+                true,
+                vec!["l", "f", "d"],
+            ),
+            vec![
+                Keyword::Base("abstract".into()),
+                Keyword::Base("assert".into()),
+                Keyword::Base("break".into()),
+                Keyword::Base("case".into()),
+                Keyword::Base("catch".into()),
+                Keyword::Base("class".into()),
+                Keyword::Base("const".into()),
+                Keyword::Base("continue".into()),
+                Keyword::Base("default".into()),
+                Keyword::Base("do".into()),
+                Keyword::Base("else".into()),
+                Keyword::Base("enum".into()),
+                Keyword::Base("extends".into()),
+                Keyword::Base("final".into()),
+                Keyword::Base("finally".into()),
+                Keyword::Base("for".into()),
+                Keyword::Base("goto".into()),
+                Keyword::Base("if".into()),
+                Keyword::Base("implements".into()),
+                Keyword::Base("import".into()),
+                Keyword::Base("instanceof".into()),
+                Keyword::Base("interface".into()),
+                Keyword::Base("native".into()),
+                Keyword::Base("new".into()),
+                Keyword::Base("package".into()),
+                Keyword::Base("private".into()),
+                Keyword::Base("protected".into()),
+                Keyword::Base("public".into()),
+                Keyword::Base("return".into()),
+                Keyword::Base("static".into()),
+                Keyword::Base("strictfp".into()),
+                Keyword::Base("super".into()),
+                Keyword::Base("switch".into()),
+                Keyword::Base("synchronized".into()),
+                Keyword::Base("this".into()),
+                Keyword::Base("throw".into()),
+                Keyword::Base("throws".into()),
+                Keyword::Base("transient".into()),
+                Keyword::Base("try".into()),
+                Keyword::Base("volatile".into()),
+                Keyword::Base("while".into()),
+                Keyword::Base("var".into()),
+                Keyword::Base("record".into()),
+                Keyword::Base("yield".into()),
+                Keyword::Base("sealed".into()),
+                Keyword::Base("permits".into()),
+                Keyword::Type("boolean".into()),
+                Keyword::Type("byte".into()),
+                Keyword::Type("char".into()),
+                Keyword::Type("double".into()),
+                Keyword::Type("float".into()),
+                Keyword::Type("int".into()),
+                Keyword::Type("long".into()),
+                Keyword::Type("short".into()),
+                Keyword::Type("void".into()),
+                Keyword::Type("String".into()),
+                Keyword::Type("Object".into()),
+                Keyword::Type("Integer".into()),
+                Keyword::Type("Long".into()),
+                Keyword::Type("Double".into()),
+                Keyword::Type("Boolean".into()),
+                Keyword::BuiltinVar("true".into()),
+                Keyword::BuiltinVar("false".into()),
+                Keyword::BuiltinVar("null".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Html,
+            FileMatch::new(vec!["html", "htm", "xhtml", "xml", "svg"], vec![], vec![]),
+            NUMBERS | STRINGS | TAGS,
+            Comment::new(vec![], Some(("<!--", "-->"))),
+            Indent::new(IndentStyle::Spaces(2), vec![], vec![]),
+            Number::new(vec![], None, false, vec![]),
+            vec![],
+            None,
+        ),
+        Syntax::new(
+            FileType::Css,
+            FileMatch::new(vec!["css", "scss", "less"], vec![], vec![]),
+            NUMBERS | STRINGS | KEYS | BRACKETS,
+            Comment::new(vec![], Some(("/*", "*/"))),
+            Indent::new(IndentStyle::Spaces(2), vec!["{", "(", "["], vec!["}", ")", "]"]),
+            Number::new(
+                vec![],
+                None,
+                true,
+                vec![
+                    "px", "em", "rem", "vh", "vw", "vmin", "vmax", "ch", "ex", "pt", "pc", "cm",
+                    "mm", "in", "deg", "rad", "turn", "ms", "s", "fr", "dpi",
+                ],
+            ),
+            vec![
+                Keyword::Base("@media".into()),
+                Keyword::Base("@import".into()),
+                Keyword::Base("@font-face".into()),
+                Keyword::Base("@keyframes".into()),
+                Keyword::Base("@supports".into()),
+                Keyword::Base("@charset".into()),
+                Keyword::Base("!important".into()),
+                Keyword::BuiltinFn("url".into()),
+                Keyword::BuiltinFn("rgb".into()),
+                Keyword::BuiltinFn("rgba".into()),
+                Keyword::BuiltinFn("hsl".into()),
+                Keyword::BuiltinFn("hsla".into()),
+                Keyword::BuiltinFn("calc".into()),
+                Keyword::BuiltinFn("var".into()),
+                Keyword::BuiltinFn("attr".into()),
+                Keyword::BuiltinFn("linear-gradient".into()),
+                Keyword::BuiltinFn("radial-gradient".into()),
+                Keyword::BuiltinVar("inherit".into()),
+                Keyword::BuiltinVar("initial".into()),
+                Keyword::BuiltinVar("unset".into()),
+                Keyword::BuiltinVar("auto".into()),
+                Keyword::BuiltinVar("none".into()),
+            ],
+            None,
+        ),
+        Syntax::new(
+            FileType::Diff,
+            FileMatch::new(vec!["diff", "patch", "rej"], vec![], vec![]),
+            DIFF,
+            Comment::new(vec![], None),
+            Indent::new(IndentStyle::Tabs, vec![], vec![]),
+            Number::new(vec![], None, false, vec![]),
+            vec![],
+            None,
         ),
     ];
 }
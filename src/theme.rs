@@ -0,0 +1,730 @@
+use crossterm::style;
+use crossterm::style::Color;
+use lazy_static::lazy_static;
+use std::env;
+
+use crate::highlight::*;
+
+pub type StyleFlags = u32;
+pub const BOLD: StyleFlags = 1 << 0;
+pub const ITALIC: StyleFlags = 1 << 1;
+pub const UNDERLINE: StyleFlags = 1 << 2;
+
+// Anything on the screen that can be styled by a theme
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Element {
+    Text(Highlight),
+    StatusBar,
+    MessageBar,
+    LineNumber,
+    Tilde,
+    Selection,
+    MatchingBracket,
+    UnbalancedBracket,
+}
+
+/*
+ * A full style for an element. Colors that are not set fall back to those
+ * of normal text, which in turn fall back to the terminal defaults.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub flags: StyleFlags,
+}
+
+impl Style {
+    pub fn new(fg: Option<Color>, bg: Option<Color>, flags: StyleFlags) -> Self {
+        Self { fg, bg, flags }
+    }
+
+    // Put this style over 'base', keeping the colors of 'base' that are not set
+    pub fn over(&self, base: &Style) -> Style {
+        Self::new(
+            self.fg.or(base.fg),
+            self.bg.or(base.bg),
+            self.flags | base.flags,
+        )
+    }
+
+    pub fn colors(&self, base: &Style, truecolor: bool) -> style::Colors {
+        let convert = |c: Option<Color>| match c {
+            Some(c) if !truecolor => rgb_to_ansi(c),
+            Some(c) => c,
+            None => Color::Reset,
+        };
+        style::Colors::new(convert(self.fg.or(base.fg)), convert(self.bg.or(base.bg)))
+    }
+
+    pub fn attributes(&self) -> Vec<style::Attribute> {
+        let mut attrs = Vec::new();
+        if self.flags & BOLD != 0 {
+            attrs.push(style::Attribute::Bold);
+        }
+        if self.flags & ITALIC != 0 {
+            attrs.push(style::Attribute::Italic);
+        }
+        if self.flags & UNDERLINE != 0 {
+            attrs.push(style::Attribute::Underlined);
+        }
+        attrs
+    }
+}
+
+/*
+ * Map a 24-bit color to the nearest color of the 6x6x6 color cube of
+ * 256 color terminals. Colors that are not RGB are returned as is.
+ */
+fn rgb_to_ansi(color: Color) -> Color {
+    match color {
+        Color::Rgb { r, g, b } => {
+            let level = |c: u8| (c as u16 * 5 + 127) / 255;
+            Color::AnsiValue((16 + 36 * level(r) + 6 * level(g) + level(b)) as u8)
+        }
+        _ => color,
+    }
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+pub struct Theme {
+    pub name: String,
+    styles: Vec<(Element, Style)>,
+}
+
+impl Theme {
+    pub fn new(name: &str, styles: Vec<(Element, Style)>) -> Self {
+        Self {
+            name: name.to_string(),
+            styles,
+        }
+    }
+
+    pub fn style(&self, element: Element) -> Style {
+        for (el, style) in self.styles.iter() {
+            if *el == element {
+                return *style;
+            }
+        }
+        Style::default()
+    }
+
+    // Style of normal text, which other styles are layered on
+    pub fn normal(&self) -> Style {
+        self.style(Element::Text(Highlight::Normal))
+    }
+
+    pub fn truecolor_supported() -> bool {
+        matches!(
+            env::var("COLORTERM").as_deref(),
+            Ok("truecolor") | Ok("24bit")
+        )
+    }
+}
+
+// Built-in themes. The first one is the default.
+lazy_static! {
+    pub static ref THEMES: Vec<Theme> = vec![
+        Theme::new(
+            "default",
+            vec![
+                (
+                    Element::Text(Highlight::Comment),
+                    Style::new(Some(Color::Cyan), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBase),
+                    Style::new(Some(Color::Yellow), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordType),
+                    Style::new(Some(Color::DarkYellow), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinVar),
+                    Style::new(Some(Color::DarkMagenta), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinFn),
+                    Style::new(Some(Color::DarkGreen), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Number),
+                    Style::new(Some(Color::Red), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Str),
+                    Style::new(Some(Color::Magenta), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Match),
+                    Style::new(Some(Color::Blue), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Key),
+                    Style::new(Some(Color::DarkCyan), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Heading),
+                    Style::new(Some(Color::Yellow), None, BOLD)
+                ),
+                (
+                    Element::Text(Highlight::Emphasis),
+                    Style::new(None, None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::Code),
+                    Style::new(Some(Color::Green), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffAdd),
+                    Style::new(Some(Color::Green), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffDelete),
+                    Style::new(Some(Color::Red), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffHeader),
+                    Style::new(Some(Color::Cyan), None, BOLD)
+                ),
+                (
+                    Element::StatusBar,
+                    Style::new(Some(Color::Black), Some(Color::White), 0)
+                ),
+                (
+                    Element::MessageBar,
+                    Style::new(Some(Color::Black), Some(Color::White), 0)
+                ),
+                (
+                    Element::Selection,
+                    Style::new(None, Some(Color::DarkGrey), 0)
+                ),
+                (
+                    Element::MatchingBracket,
+                    Style::new(None, Some(Color::DarkCyan), BOLD)
+                ),
+                (
+                    Element::UnbalancedBracket,
+                    Style::new(Some(Color::White), Some(Color::Red), BOLD)
+                ),
+            ],
+        ),
+        Theme::new(
+            "gruvbox-dark",
+            vec![
+                (
+                    Element::Text(Highlight::Normal),
+                    Style::new(Some(rgb(0xeb, 0xdb, 0xb2)), Some(rgb(0x28, 0x28, 0x28)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Comment),
+                    Style::new(Some(rgb(0x92, 0x83, 0x74)), None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBase),
+                    Style::new(Some(rgb(0xfb, 0x49, 0x34)), None, BOLD)
+                ),
+                (
+                    Element::Text(Highlight::KeywordType),
+                    Style::new(Some(rgb(0xfa, 0xbd, 0x2f)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinVar),
+                    Style::new(Some(rgb(0xfe, 0x80, 0x19)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinFn),
+                    Style::new(Some(rgb(0x8e, 0xc0, 0x7c)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Number),
+                    Style::new(Some(rgb(0xd3, 0x86, 0x9b)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Str),
+                    Style::new(Some(rgb(0xb8, 0xbb, 0x26)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Match),
+                    Style::new(Some(rgb(0x28, 0x28, 0x28)), Some(rgb(0xfa, 0xbd, 0x2f)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Key),
+                    Style::new(Some(rgb(0x83, 0xa5, 0x98)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Heading),
+                    Style::new(Some(rgb(0xfa, 0xbd, 0x2f)), None, BOLD)
+                ),
+                (
+                    Element::Text(Highlight::Emphasis),
+                    Style::new(None, None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::Code),
+                    Style::new(Some(rgb(0x8e, 0xc0, 0x7c)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffAdd),
+                    Style::new(Some(rgb(0xb8, 0xbb, 0x26)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffDelete),
+                    Style::new(Some(rgb(0xfb, 0x49, 0x34)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffHeader),
+                    Style::new(Some(rgb(0x83, 0xa5, 0x98)), None, BOLD)
+                ),
+                (
+                    Element::StatusBar,
+                    Style::new(
+                        Some(rgb(0xeb, 0xdb, 0xb2)),
+                        Some(rgb(0x50, 0x49, 0x45)),
+                        BOLD
+                    )
+                ),
+                (
+                    Element::MessageBar,
+                    Style::new(Some(rgb(0xeb, 0xdb, 0xb2)), Some(rgb(0x3c, 0x38, 0x36)), 0)
+                ),
+                (
+                    Element::LineNumber,
+                    Style::new(Some(rgb(0x7c, 0x6f, 0x64)), None, 0)
+                ),
+                (
+                    Element::Tilde,
+                    Style::new(Some(rgb(0x66, 0x5c, 0x54)), None, 0)
+                ),
+                (
+                    Element::Selection,
+                    Style::new(None, Some(rgb(0x50, 0x49, 0x45)), 0)
+                ),
+                (
+                    Element::MatchingBracket,
+                    Style::new(None, Some(rgb(0x66, 0x5c, 0x54)), BOLD)
+                ),
+                (
+                    Element::UnbalancedBracket,
+                    Style::new(
+                        Some(rgb(0xfb, 0xf1, 0xc7)),
+                        Some(rgb(0xcc, 0x24, 0x1d)),
+                        BOLD
+                    )
+                ),
+            ],
+        ),
+        Theme::new(
+            "monokai",
+            vec![
+                (
+                    Element::Text(Highlight::Normal),
+                    Style::new(Some(rgb(0xf8, 0xf8, 0xf2)), Some(rgb(0x27, 0x28, 0x22)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Comment),
+                    Style::new(Some(rgb(0x75, 0x71, 0x5e)), None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBase),
+                    Style::new(Some(rgb(0xf9, 0x26, 0x72)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordType),
+                    Style::new(Some(rgb(0x66, 0xd9, 0xef)), None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinVar),
+                    Style::new(Some(rgb(0xfd, 0x97, 0x1f)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinFn),
+                    Style::new(Some(rgb(0xa6, 0xe2, 0x2e)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Number),
+                    Style::new(Some(rgb(0xae, 0x81, 0xff)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Str),
+                    Style::new(Some(rgb(0xe6, 0xdb, 0x74)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Match),
+                    Style::new(Some(rgb(0x27, 0x28, 0x22)), Some(rgb(0xe6, 0xdb, 0x74)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Key),
+                    Style::new(Some(rgb(0x66, 0xd9, 0xef)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Heading),
+                    Style::new(Some(rgb(0xa6, 0xe2, 0x2e)), None, BOLD)
+                ),
+                (
+                    Element::Text(Highlight::Emphasis),
+                    Style::new(None, None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::Code),
+                    Style::new(Some(rgb(0xe6, 0xdb, 0x74)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffAdd),
+                    Style::new(Some(rgb(0xa6, 0xe2, 0x2e)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffDelete),
+                    Style::new(Some(rgb(0xf9, 0x26, 0x72)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffHeader),
+                    Style::new(Some(rgb(0xfd, 0x97, 0x1f)), None, BOLD)
+                ),
+                (
+                    Element::StatusBar,
+                    Style::new(Some(rgb(0xf8, 0xf8, 0xf2)), Some(rgb(0x49, 0x48, 0x3e)), 0)
+                ),
+                (
+                    Element::MessageBar,
+                    Style::new(Some(rgb(0xf8, 0xf8, 0xf2)), Some(rgb(0x3e, 0x3d, 0x32)), 0)
+                ),
+                (
+                    Element::LineNumber,
+                    Style::new(Some(rgb(0x90, 0x90, 0x8a)), None, 0)
+                ),
+                (
+                    Element::Tilde,
+                    Style::new(Some(rgb(0x75, 0x71, 0x5e)), None, 0)
+                ),
+                (
+                    Element::Selection,
+                    Style::new(None, Some(rgb(0x49, 0x48, 0x3e)), 0)
+                ),
+                (
+                    Element::MatchingBracket,
+                    Style::new(None, Some(rgb(0x75, 0x71, 0x5e)), BOLD)
+                ),
+                (
+                    Element::UnbalancedBracket,
+                    Style::new(
+                        Some(rgb(0xf8, 0xf8, 0xf2)),
+                        Some(rgb(0xf9, 0x26, 0x72)),
+                        BOLD
+                    )
+                ),
+            ],
+        ),
+        Theme::new(
+            "solarized-dark",
+            vec![
+                (
+                    Element::Text(Highlight::Normal),
+                    Style::new(Some(rgb(0x83, 0x94, 0x96)), Some(rgb(0x00, 0x2b, 0x36)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Comment),
+                    Style::new(Some(rgb(0x58, 0x6e, 0x75)), None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBase),
+                    Style::new(Some(rgb(0x85, 0x99, 0x00)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordType),
+                    Style::new(Some(rgb(0xb5, 0x89, 0x00)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinVar),
+                    Style::new(Some(rgb(0x6c, 0x71, 0xc4)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinFn),
+                    Style::new(Some(rgb(0x26, 0x8b, 0xd2)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Number),
+                    Style::new(Some(rgb(0xd3, 0x36, 0x82)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Str),
+                    Style::new(Some(rgb(0x2a, 0xa1, 0x98)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Match),
+                    Style::new(Some(rgb(0x00, 0x2b, 0x36)), Some(rgb(0xb5, 0x89, 0x00)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Key),
+                    Style::new(Some(rgb(0x26, 0x8b, 0xd2)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Heading),
+                    Style::new(Some(rgb(0xcb, 0x4b, 0x16)), None, BOLD)
+                ),
+                (
+                    Element::Text(Highlight::Emphasis),
+                    Style::new(None, None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::Code),
+                    Style::new(Some(rgb(0x2a, 0xa1, 0x98)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffAdd),
+                    Style::new(Some(rgb(0x85, 0x99, 0x00)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffDelete),
+                    Style::new(Some(rgb(0xdc, 0x32, 0x2f)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffHeader),
+                    Style::new(Some(rgb(0x26, 0x8b, 0xd2)), None, BOLD)
+                ),
+                (
+                    Element::StatusBar,
+                    Style::new(Some(rgb(0x93, 0xa1, 0xa1)), Some(rgb(0x07, 0x36, 0x42)), 0)
+                ),
+                (
+                    Element::MessageBar,
+                    Style::new(Some(rgb(0x93, 0xa1, 0xa1)), Some(rgb(0x07, 0x36, 0x42)), 0)
+                ),
+                (
+                    Element::LineNumber,
+                    Style::new(Some(rgb(0x58, 0x6e, 0x75)), None, 0)
+                ),
+                (
+                    Element::Tilde,
+                    Style::new(Some(rgb(0x58, 0x6e, 0x75)), None, 0)
+                ),
+                (
+                    Element::Selection,
+                    Style::new(None, Some(rgb(0x07, 0x36, 0x42)), 0)
+                ),
+                (
+                    Element::MatchingBracket,
+                    Style::new(None, Some(rgb(0x58, 0x6e, 0x75)), BOLD)
+                ),
+                (
+                    Element::UnbalancedBracket,
+                    Style::new(
+                        Some(rgb(0xfd, 0xf6, 0xe3)),
+                        Some(rgb(0xdc, 0x32, 0x2f)),
+                        BOLD
+                    )
+                ),
+            ],
+        ),
+        Theme::new(
+            "solarized-light",
+            vec![
+                (
+                    Element::Text(Highlight::Normal),
+                    Style::new(Some(rgb(0x65, 0x7b, 0x83)), Some(rgb(0xfd, 0xf6, 0xe3)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Comment),
+                    Style::new(Some(rgb(0x93, 0xa1, 0xa1)), None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBase),
+                    Style::new(Some(rgb(0x85, 0x99, 0x00)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordType),
+                    Style::new(Some(rgb(0xb5, 0x89, 0x00)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinVar),
+                    Style::new(Some(rgb(0x6c, 0x71, 0xc4)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinFn),
+                    Style::new(Some(rgb(0x26, 0x8b, 0xd2)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Number),
+                    Style::new(Some(rgb(0xd3, 0x36, 0x82)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Str),
+                    Style::new(Some(rgb(0x2a, 0xa1, 0x98)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Match),
+                    Style::new(Some(rgb(0xfd, 0xf6, 0xe3)), Some(rgb(0xb5, 0x89, 0x00)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Key),
+                    Style::new(Some(rgb(0x26, 0x8b, 0xd2)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Heading),
+                    Style::new(Some(rgb(0xcb, 0x4b, 0x16)), None, BOLD)
+                ),
+                (
+                    Element::Text(Highlight::Emphasis),
+                    Style::new(None, None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::Code),
+                    Style::new(Some(rgb(0x2a, 0xa1, 0x98)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffAdd),
+                    Style::new(Some(rgb(0x85, 0x99, 0x00)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffDelete),
+                    Style::new(Some(rgb(0xdc, 0x32, 0x2f)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffHeader),
+                    Style::new(Some(rgb(0x26, 0x8b, 0xd2)), None, BOLD)
+                ),
+                (
+                    Element::StatusBar,
+                    Style::new(Some(rgb(0x58, 0x6e, 0x75)), Some(rgb(0xee, 0xe8, 0xd5)), 0)
+                ),
+                (
+                    Element::MessageBar,
+                    Style::new(Some(rgb(0x58, 0x6e, 0x75)), Some(rgb(0xee, 0xe8, 0xd5)), 0)
+                ),
+                (
+                    Element::LineNumber,
+                    Style::new(Some(rgb(0x93, 0xa1, 0xa1)), None, 0)
+                ),
+                (
+                    Element::Tilde,
+                    Style::new(Some(rgb(0x93, 0xa1, 0xa1)), None, 0)
+                ),
+                (
+                    Element::Selection,
+                    Style::new(None, Some(rgb(0xee, 0xe8, 0xd5)), 0)
+                ),
+                (
+                    Element::MatchingBracket,
+                    Style::new(None, Some(rgb(0x93, 0xa1, 0xa1)), BOLD)
+                ),
+                (
+                    Element::UnbalancedBracket,
+                    Style::new(
+                        Some(rgb(0xfd, 0xf6, 0xe3)),
+                        Some(rgb(0xdc, 0x32, 0x2f)),
+                        BOLD
+                    )
+                ),
+            ],
+        ),
+        Theme::new(
+            "gruvbox-light",
+            vec![
+                (
+                    Element::Text(Highlight::Normal),
+                    Style::new(Some(rgb(0x3c, 0x38, 0x36)), Some(rgb(0xfb, 0xf1, 0xc7)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Comment),
+                    Style::new(Some(rgb(0x92, 0x83, 0x74)), None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBase),
+                    Style::new(Some(rgb(0x9d, 0x00, 0x06)), None, BOLD)
+                ),
+                (
+                    Element::Text(Highlight::KeywordType),
+                    Style::new(Some(rgb(0xb5, 0x76, 0x14)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinVar),
+                    Style::new(Some(rgb(0xaf, 0x3a, 0x03)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::KeywordBuiltinFn),
+                    Style::new(Some(rgb(0x42, 0x7b, 0x58)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Number),
+                    Style::new(Some(rgb(0x8f, 0x3f, 0x71)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Str),
+                    Style::new(Some(rgb(0x79, 0x74, 0x0e)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Match),
+                    Style::new(Some(rgb(0xfb, 0xf1, 0xc7)), Some(rgb(0xb5, 0x76, 0x14)), 0)
+                ),
+                (
+                    Element::Text(Highlight::Key),
+                    Style::new(Some(rgb(0x07, 0x66, 0x78)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::Heading),
+                    Style::new(Some(rgb(0xb5, 0x76, 0x14)), None, BOLD)
+                ),
+                (
+                    Element::Text(Highlight::Emphasis),
+                    Style::new(None, None, ITALIC)
+                ),
+                (
+                    Element::Text(Highlight::Code),
+                    Style::new(Some(rgb(0x42, 0x7b, 0x58)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffAdd),
+                    Style::new(Some(rgb(0x79, 0x74, 0x0e)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffDelete),
+                    Style::new(Some(rgb(0x9d, 0x00, 0x06)), None, 0)
+                ),
+                (
+                    Element::Text(Highlight::DiffHeader),
+                    Style::new(Some(rgb(0x07, 0x66, 0x78)), None, BOLD)
+                ),
+                (
+                    Element::StatusBar,
+                    Style::new(
+                        Some(rgb(0x3c, 0x38, 0x36)),
+                        Some(rgb(0xd5, 0xc4, 0xa1)),
+                        BOLD
+                    )
+                ),
+                (
+                    Element::MessageBar,
+                    Style::new(Some(rgb(0x3c, 0x38, 0x36)), Some(rgb(0xeb, 0xdb, 0xb2)), 0)
+                ),
+                (
+                    Element::LineNumber,
+                    Style::new(Some(rgb(0xa8, 0x99, 0x84)), None, 0)
+                ),
+                (
+                    Element::Tilde,
+                    Style::new(Some(rgb(0xbd, 0xae, 0x93)), None, 0)
+                ),
+                (
+                    Element::Selection,
+                    Style::new(None, Some(rgb(0xd5, 0xc4, 0xa1)), 0)
+                ),
+                (
+                    Element::MatchingBracket,
+                    Style::new(None, Some(rgb(0xbd, 0xae, 0x93)), BOLD)
+                ),
+                (
+                    Element::UnbalancedBracket,
+                    Style::new(
+                        Some(rgb(0xfb, 0xf1, 0xc7)),
+                        Some(rgb(0xcc, 0x24, 0x1d)),
+                        BOLD
+                    )
+                ),
+            ],
+        ),
+    ];
+}
@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+
+use streaming_iterator::StreamingIterator;
+use tree_sitter::InputEdit;
+use tree_sitter::Language;
+use tree_sitter::Parser;
+use tree_sitter::Point;
+use tree_sitter::Query;
+use tree_sitter::QueryCursor;
+use tree_sitter::Tree;
+
+use crate::data::*;
+use crate::highlight::*;
+use crate::syntax::*;
+
+/*
+ * Highlighting backend based on tree-sitter grammars. The buffer is parsed
+ * into a syntax tree which is updated incrementally on every edit, and the
+ * captures of the highlight query of the grammar are mapped to highlight
+ * categories. Languages without a grammar use the keyword highlighter.
+ */
+pub struct TreeSitter {
+    parser: Parser,
+    query: Query,
+    highlights: Vec<Option<Highlight>>, // highlight for each capture name of the query
+    tree: Option<Tree>,
+    source: String, // text of the buffer the tree was parsed from
+}
+
+impl TreeSitter {
+    // Create a backend for the syntax, or None if there is no grammar for it
+    pub fn new(syntax: &Syntax) -> Option<Self> {
+        let (language, query): (Language, &str) = match syntax.filetype {
+            FileType::C => (
+                tree_sitter_c::LANGUAGE.into(),
+                tree_sitter_c::HIGHLIGHT_QUERY,
+            ),
+            FileType::Rust => (
+                tree_sitter_rust::LANGUAGE.into(),
+                tree_sitter_rust::HIGHLIGHTS_QUERY,
+            ),
+            FileType::Python => (
+                tree_sitter_python::LANGUAGE.into(),
+                tree_sitter_python::HIGHLIGHTS_QUERY,
+            ),
+            _ => return None,
+        };
+        let mut parser = Parser::new();
+        parser.set_language(&language).ok()?;
+        let query = Query::new(&language, query).ok()?;
+        let highlights = query
+            .capture_names()
+            .iter()
+            .map(|name| Self::capture_highlight(name))
+            .collect();
+
+        Some(Self {
+            parser,
+            query,
+            highlights,
+            tree: None,
+            source: String::new(),
+        })
+    }
+
+    /*
+     * Map the name of a capture like 'function.method' to a highlight. Names
+     * without a highlight, like 'variable' or 'punctuation', are left normal.
+     */
+    fn capture_highlight(name: &str) -> Option<Highlight> {
+        match name.split('.').next().unwrap_or(name) {
+            "comment" => Some(Highlight::Comment),
+            "keyword" => Some(Highlight::KeywordBase),
+            "type" | "constructor" => Some(Highlight::KeywordType),
+            "function" => Some(Highlight::KeywordBuiltinFn),
+            "constant" | "label" | "attribute" => Some(Highlight::KeywordBuiltinVar),
+            "variable" if name == "variable.builtin" => Some(Highlight::KeywordBuiltinVar),
+            "number" => Some(Highlight::Number),
+            "string" | "escape" => Some(Highlight::Str),
+            "property" => Some(Highlight::Key),
+            _ => None,
+        }
+    }
+
+    // Grammars capture number literals as constants, but they have their own highlight
+    fn is_number(kind: &str) -> bool {
+        matches!(
+            kind,
+            "integer_literal" | "float_literal" | "number_literal" | "integer" | "float"
+        )
+    }
+
+    /*
+     * Parse the buffer again after an edit. The edit is found by comparing
+     * the text with the one of the last parse, so that the unchanged parts
+     * of the old tree are reused. Only the edited rows and the rows where
+     * the syntax tree changed are highlighted again.
+     */
+    pub fn update(&mut self, rows: &mut [EditRow]) {
+        let source = rows
+            .iter()
+            .map(|row| row.chars.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+
+        let edit = match &mut self.tree {
+            Some(tree) => {
+                if source == self.source {
+                    return;
+                }
+                let edit = Self::input_edit(&self.source, &source);
+                tree.edit(&edit);
+                Some(edit)
+            }
+            None => None,
+        };
+
+        let new_tree = match self.parser.parse(&source, self.tree.as_ref()) {
+            Some(tree) => tree,
+            None => return,
+        };
+
+        let (mut first, mut last) = match edit {
+            Some(edit) => (edit.start_position.row, edit.new_end_position.row),
+            None => (0, rows.len()),
+        };
+        if let Some(old_tree) = &self.tree {
+            for range in old_tree.changed_ranges(&new_tree) {
+                first = first.min(range.start_point.row);
+                last = last.max(range.end_point.row);
+            }
+        }
+        self.tree = Some(new_tree);
+        self.source = source;
+
+        if !rows.is_empty() {
+            let last = last.min(rows.len() - 1);
+            self.highlight_rows(rows, first.min(last), last);
+        }
+    }
+
+    /*
+     * Highlight all the rows again, like after their tab stop changed and
+     * their highlight was reset. The text is only parsed again if it changed.
+     */
+    pub fn update_all(&mut self, rows: &mut [EditRow]) {
+        // The first parse highlights all the rows already
+        let parsed = self.tree.is_some();
+        self.update(rows);
+        if parsed && !rows.is_empty() {
+            self.highlight_rows(rows, 0, rows.len() - 1);
+        }
+    }
+
+    fn highlight_rows(&self, rows: &mut [EditRow], first: usize, last: usize) {
+        let tree = match &self.tree {
+            Some(tree) => tree,
+            None => return,
+        };
+        for row in rows[first..=last].iter_mut() {
+            row.highlight.fill(Highlight::Normal);
+        }
+
+        let mut cursor = QueryCursor::new();
+        cursor.set_point_range(Point::new(first, 0)..Point::new(last + 1, 0));
+        let mut captures = cursor.captures(&self.query, tree.root_node(), self.source.as_bytes());
+        // The first pattern that captures a node decides its highlight
+        let mut seen = HashSet::new();
+
+        while let Some((m, idx)) = captures.next() {
+            let capture = m.captures[*idx];
+            let node = capture.node;
+            if !seen.insert(node.id()) {
+                continue;
+            }
+            let hl = if Self::is_number(node.kind()) {
+                Some(Highlight::Number)
+            } else {
+                self.highlights[capture.index as usize]
+            };
+            if let Some(hl) = hl {
+                let start = node.start_position();
+                let end = node.end_position();
+                let (top, bottom) = (start.row.max(first), end.row.min(last));
+                for (y, row) in rows.iter_mut().enumerate().take(bottom + 1).skip(top) {
+                    let from = if y == start.row { start.column } else { 0 };
+                    let to = if y == end.row {
+                        end.column
+                    } else {
+                        row.chars.len()
+                    };
+                    Self::fill_columns(row, from, to, hl);
+                }
+            }
+        }
+    }
+
+    // Highlight the part of a row between the byte columns 'from' and 'to'
+    fn fill_columns(row: &mut EditRow, from: usize, to: usize, hl: Highlight) {
+        let to = to.min(row.chars.len());
+        if from >= to {
+            return;
+        }
+        let cx_from = row.chars[..from].chars().count() as u16;
+        let cx_to = row.chars[..to].chars().count() as u16;
+        let rx_from = row.cx_to_rx(cx_from) as usize;
+        let rx_to = (row.cx_to_rx(cx_to) as usize).min(row.highlight.len());
+        if rx_from < rx_to {
+            row.highlight[rx_from..rx_to].fill(hl);
+        }
+    }
+
+    /*
+     * Describe the change from 'old' to 'new' as a single edit, replacing
+     * everything between their common prefix and common suffix.
+     */
+    fn input_edit(old: &str, new: &str) -> InputEdit {
+        let mut prefix = old
+            .bytes()
+            .zip(new.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+            prefix -= 1;
+        }
+        let max_suffix = old.len().min(new.len()) - prefix;
+        let mut suffix = old
+            .bytes()
+            .rev()
+            .zip(new.bytes().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix)
+        {
+            suffix -= 1;
+        }
+
+        let old_end = old.len() - suffix;
+        let new_end = new.len() - suffix;
+        InputEdit {
+            start_byte: prefix,
+            old_end_byte: old_end,
+            new_end_byte: new_end,
+            start_position: Self::point(old, prefix),
+            old_end_position: Self::point(old, old_end),
+            new_end_position: Self::point(new, new_end),
+        }
+    }
+
+    fn point(text: &str, byte: usize) -> Point {
+        let before = &text[..byte];
+        let row = before.matches('\n').count();
+        let column = match before.rfind('\n') {
+            Some(idx) => byte - idx - 1,
+            None => byte,
+        };
+        Point::new(row, column)
+    }
+}
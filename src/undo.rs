@@ -0,0 +1,128 @@
+use crate::dimensions::*;
+
+// Number of changes that can be undone
+const UNDO_LIMIT: usize = 1000;
+
+/*
+ * A change of the buffer: the rows starting at 'at' were replaced, 'old'
+ * holds them before and 'new' after the change. Applying it backwards
+ * undoes the change, applying it forwards again redoes it.
+ */
+#[derive(Clone)]
+pub struct Change {
+    pub at: usize,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+    pub cursor_before: Position,
+    pub cursor_after: Position,
+    pub rows_before: usize, // number of rows in the buffer before the change
+    typing: bool,           // single characters typed on a row, merged into one step
+}
+
+impl Change {
+    pub fn new(at: usize, old: Vec<String>, cursor: Position, rows_before: usize) -> Self {
+        Self {
+            at,
+            old,
+            new: Vec::new(),
+            cursor_before: cursor,
+            cursor_after: cursor,
+            rows_before,
+            typing: false,
+        }
+    }
+
+    // Mark the change as typing, so that it can be merged with the next characters typed
+    pub fn typing(mut self) -> Self {
+        self.typing = true;
+        self
+    }
+
+    // The same change in the other direction
+    pub fn inverse(&self) -> Self {
+        Self {
+            at: self.at,
+            old: self.new.clone(),
+            new: self.old.clone(),
+            cursor_before: self.cursor_after,
+            cursor_after: self.cursor_before,
+            rows_before: self.rows_before + self.new.len() - self.old.len(),
+            typing: false,
+        }
+    }
+}
+
+pub struct UndoStack {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /*
+     * Add a change that was made to the buffer. Characters typed one after
+     * the other on the same row extend the last change instead, until the
+     * cursor moves elsewhere or a word ends.
+     */
+    pub fn push(&mut self, change: Change) {
+        self.redo.clear();
+        if let Some(last) = self.undo.last_mut() {
+            let continues = last.typing
+                && change.typing
+                && last.at == change.at
+                && last.new.len() == 1
+                && change.old.len() == 1
+                && last.cursor_after.x == change.cursor_before.x
+                && last.cursor_after.y == change.cursor_before.y
+                && !Self::ends_word(&change);
+            if continues {
+                last.new = change.new;
+                last.cursor_after = change.cursor_after;
+                return;
+            }
+        }
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(change);
+    }
+
+    // Typing a space starts a new undo step, so that undo removes a word at a time
+    fn ends_word(change: &Change) -> bool {
+        let x = change.cursor_before.x as usize;
+        change
+            .new
+            .first()
+            .and_then(|row| row.chars().nth(x))
+            .is_some_and(char::is_whitespace)
+    }
+
+    // Stop merging typed characters into the last change
+    pub fn seal(&mut self) {
+        if let Some(last) = self.undo.last_mut() {
+            last.typing = false;
+        }
+    }
+
+    // Return the change that undoes the last one
+    pub fn undo(&mut self) -> Option<Change> {
+        let mut change = self.undo.pop()?;
+        change.typing = false;
+        let inverse = change.inverse();
+        self.redo.push(change);
+        Some(inverse)
+    }
+
+    // Return the last undone change to apply it again
+    pub fn redo(&mut self) -> Option<Change> {
+        let change = self.redo.pop()?;
+        self.undo.push(change.clone());
+        Some(change)
+    }
+}
@@ -0,0 +1,380 @@
+use crate::events::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Insert,
+    Visual,
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Normal => "NORMAL",
+            Mode::Insert => "INSERT",
+            Mode::Visual => "VISUAL",
+        }
+    }
+}
+
+// Largest count of a command, larger counts typed are cut to it
+const MAX_COUNT: usize = 1_000_000;
+
+// Text that was deleted or yanked, to be put back with 'p'
+#[derive(Default)]
+pub struct Register {
+    pub text: String,
+    pub linewise: bool, // whole lines, put back above or below the line of the cursor
+}
+
+// Result of parsing the keys typed in normal or visual mode so far
+enum Parsed {
+    Pending,
+    Invalid,
+    Event(EditorEvent),
+    Repeat(Option<usize>),
+}
+
+/*
+ * The modal layer of the vi editing mode. Keys typed in normal and visual
+ * mode are collected until they make up a command like '3dw' or 'gg'. In
+ * insert mode keys go through the keymap as usual. The last change, along
+ * with the text typed after it in insert mode, is kept for '.' to repeat.
+ */
+pub struct Vi {
+    mode: Mode,
+    keys: Vec<char>,               // keys of an unfinished command
+    last_change: Vec<EditorEvent>, // events of the last change, for '.'
+    recording: bool,               // insert mode is adding to last_change
+}
+
+impl Vi {
+    pub fn new() -> Self {
+        Self {
+            mode: Mode::Normal,
+            keys: Vec::new(),
+            last_change: Vec::new(),
+            recording: false,
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    // Handle a character typed in normal or visual mode, return the events of a complete command
+    pub fn normal_key(&mut self, ch: char) -> Vec<EditorEvent> {
+        self.keys.push(ch);
+        let parsed = if self.mode == Mode::Visual {
+            Self::parse_visual(&self.keys)
+        } else {
+            Self::parse(&self.keys)
+        };
+        match parsed {
+            Parsed::Pending => Vec::new(),
+            Parsed::Invalid => {
+                self.keys.clear();
+                Vec::new()
+            }
+            Parsed::Repeat(count) => {
+                self.keys.clear();
+                if let (Some(count), Some(first)) = (count, self.last_change.first_mut()) {
+                    *first = Self::with_count(*first, count);
+                }
+                if let Some(&first) = self.last_change.first() {
+                    self.switch_mode(first);
+                }
+                self.last_change.clone()
+            }
+            Parsed::Event(event) => {
+                self.keys.clear();
+                self.enter(event);
+                vec![event]
+            }
+        }
+    }
+
+    // Esc leaves insert and visual mode, or cancels the command typed so far
+    pub fn escape(&mut self) -> EditorEvent {
+        self.keys.clear();
+        let mode = self.mode;
+        self.mode = Mode::Normal;
+        if mode == Mode::Insert {
+            let event = EditorEvent::Vi(ViCommand::NormalMode);
+            if self.recording {
+                self.last_change.push(event);
+                self.recording = false;
+            }
+            event
+        } else {
+            EditorEvent::Control(ControlEvent::Escape)
+        }
+    }
+
+    /*
+     * Adapt an event of the keymap to the mode. Insert mode records it for
+     * '.', normal mode turns editing keys into movement and visual mode
+     * turns movement into selection.
+     */
+    pub fn keymap_event(&mut self, event: EditorEvent) -> Option<EditorEvent> {
+        if self.mode == Mode::Insert {
+            if self.recording {
+                self.last_change.push(event);
+            }
+            return Some(event);
+        }
+        self.keys.clear();
+        match event {
+            EditorEvent::Key(_) => None,
+            EditorEvent::Cursor(CursorKey::Backspace) => Some(EditorEvent::Cursor(CursorKey::Left)),
+            EditorEvent::Cursor(CursorKey::Enter) => Some(EditorEvent::Cursor(CursorKey::Down)),
+            EditorEvent::Cursor(
+                CursorKey::Delete | CursorKey::DeleteWordLeft | CursorKey::DeleteWordRight,
+            ) => None,
+            EditorEvent::Cursor(key) if self.mode == Mode::Visual => Some(EditorEvent::Select(key)),
+            event => Some(event),
+        }
+    }
+
+    // Follow the mode through the events of a change repeated with '.'
+    pub fn replay(&mut self, event: EditorEvent) {
+        self.switch_mode(event);
+    }
+
+    // Switch the mode for a command and start recording it if it changes the buffer
+    fn enter(&mut self, event: EditorEvent) {
+        self.switch_mode(event);
+        let repeatable = matches!(
+            event,
+            EditorEvent::Vi(ViCommand::Operate(
+                Operator::Delete | Operator::Change,
+                _,
+                _
+            )) | EditorEvent::Vi(ViCommand::Insert(_))
+                | EditorEvent::Vi(ViCommand::Paste(_, _))
+                | EditorEvent::Control(ControlEvent::JoinLines)
+        );
+        if repeatable {
+            self.last_change = vec![event];
+            self.recording = self.mode == Mode::Insert;
+        }
+    }
+
+    fn switch_mode(&mut self, event: EditorEvent) {
+        match event {
+            EditorEvent::Vi(ViCommand::Operate(Operator::Change, _, _))
+            | EditorEvent::Vi(ViCommand::VisualOperate(Operator::Change))
+            | EditorEvent::Vi(ViCommand::Insert(_)) => self.mode = Mode::Insert,
+            EditorEvent::Vi(ViCommand::Visual) => self.mode = Mode::Visual,
+            EditorEvent::Vi(ViCommand::VisualOperate(_))
+            | EditorEvent::Vi(ViCommand::NormalMode)
+            | EditorEvent::Control(ControlEvent::Escape) => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    // The keys of a vi command, the way macros are saved
+    pub fn command_keys(command: ViCommand) -> String {
+        let count = |n: usize| if n > 1 { n.to_string() } else { String::new() };
+        let op_key = |op| match op {
+            Operator::Delete => 'd',
+            Operator::Change => 'c',
+            Operator::Yank => 'y',
+        };
+        match command {
+            ViCommand::Move(motion, n) => format!("{}{}", count(n), Self::motion_keys(motion)),
+            ViCommand::Operate(op, Motion::Lines, n) => {
+                format!("{}{}{}", count(n), op_key(op), op_key(op))
+            }
+            ViCommand::Operate(op, motion @ Motion::GotoLine(_), _) => {
+                format!("{}{}", op_key(op), Self::motion_keys(motion))
+            }
+            ViCommand::Operate(op, motion, n) => {
+                format!("{}{}{}", count(n), op_key(op), Self::motion_keys(motion))
+            }
+            ViCommand::Insert(at) => match at {
+                InsertAt::Cursor => "i",
+                InsertAt::After => "a",
+                InsertAt::LineStart => "I",
+                InsertAt::LineEnd => "A",
+                InsertAt::LineBelow => "o",
+                InsertAt::LineAbove => "O",
+            }
+            .to_string(),
+            ViCommand::NormalMode => "Esc".to_string(),
+            ViCommand::Visual => "v".to_string(),
+            ViCommand::VisualOperate(op) => format!("v{}", op_key(op)),
+            ViCommand::Paste(before, n) => {
+                format!("{}{}", count(n), if before { 'P' } else { 'p' })
+            }
+            ViCommand::Command => ":".to_string(),
+        }
+    }
+
+    fn motion_keys(motion: Motion) -> String {
+        match motion {
+            Motion::Left => "h".to_string(),
+            Motion::Right => "l".to_string(),
+            Motion::Up => "k".to_string(),
+            Motion::Down => "j".to_string(),
+            Motion::WordStart => "w".to_string(),
+            Motion::WordEnd => "e".to_string(),
+            Motion::WordBack => "b".to_string(),
+            Motion::LineStart => "0".to_string(),
+            Motion::FirstNonBlank => "^".to_string(),
+            Motion::LineEnd | Motion::Lines => "$".to_string(),
+            Motion::GotoLine(Some(line)) => format!("{}G", line),
+            Motion::GotoLine(None) => "G".to_string(),
+        }
+    }
+
+    // Parse the keys written by command_keys()
+    pub fn parse_command(keys: &str) -> Option<ViCommand> {
+        let command = match keys {
+            "Esc" => ViCommand::NormalMode,
+            "vd" => ViCommand::VisualOperate(Operator::Delete),
+            "vc" => ViCommand::VisualOperate(Operator::Change),
+            "vy" => ViCommand::VisualOperate(Operator::Yank),
+            _ => match Self::parse(&keys.chars().collect::<Vec<char>>()) {
+                Parsed::Event(EditorEvent::Vi(command)) => command,
+                _ => return None,
+            },
+        };
+        Some(command)
+    }
+
+    fn with_count(event: EditorEvent, count: usize) -> EditorEvent {
+        match event {
+            EditorEvent::Vi(ViCommand::Operate(op, motion, _)) => {
+                EditorEvent::Vi(ViCommand::Operate(op, motion, count))
+            }
+            EditorEvent::Vi(ViCommand::Paste(before, _)) => {
+                EditorEvent::Vi(ViCommand::Paste(before, count))
+            }
+            event => event,
+        }
+    }
+
+    // Split a count like the '12' of '12j' from the keys, '0' alone is a motion
+    fn take_count(keys: &[char]) -> (Option<usize>, &[char]) {
+        if keys.first().is_some_and(|&c| c == '0') {
+            return (None, keys);
+        }
+        let len = keys.iter().take_while(|c| c.is_ascii_digit()).count();
+        let count = (len > 0).then(|| {
+            let digits = keys[..len].iter().collect::<String>();
+            digits
+                .parse()
+                .map_or(MAX_COUNT, |count: usize| count.min(MAX_COUNT))
+        });
+        (count, &keys[len..])
+    }
+
+    fn parse_motion(keys: &[char], count: Option<usize>) -> Result<Option<Motion>, ()> {
+        let motion = match keys {
+            ['h'] => Motion::Left,
+            ['l'] => Motion::Right,
+            ['j'] => Motion::Down,
+            ['k'] => Motion::Up,
+            ['w'] => Motion::WordStart,
+            ['e'] => Motion::WordEnd,
+            ['b'] => Motion::WordBack,
+            ['0'] => Motion::LineStart,
+            ['^'] => Motion::FirstNonBlank,
+            ['$'] => Motion::LineEnd,
+            ['G'] => Motion::GotoLine(count),
+            ['g', 'g'] => Motion::GotoLine(Some(count.unwrap_or(1))),
+            ['g'] => return Ok(None),
+            _ => return Err(()),
+        };
+        Ok(Some(motion))
+    }
+
+    fn motion_event(
+        keys: &[char],
+        count: Option<usize>,
+        event: impl Fn(Motion) -> ViCommand,
+    ) -> Parsed {
+        match Self::parse_motion(keys, count) {
+            Ok(Some(motion)) => Parsed::Event(EditorEvent::Vi(event(motion))),
+            Ok(None) => Parsed::Pending,
+            Err(()) => Parsed::Invalid,
+        }
+    }
+
+    fn parse(keys: &[char]) -> Parsed {
+        let (count, rest) = Self::take_count(keys);
+        let n = count.unwrap_or(1);
+        let vi = |command| Parsed::Event(EditorEvent::Vi(command));
+        let control = |event| Parsed::Event(EditorEvent::Control(event));
+
+        let op = match rest.first() {
+            Some('d') => Some(Operator::Delete),
+            Some('c') => Some(Operator::Change),
+            Some('y') => Some(Operator::Yank),
+            _ => None,
+        };
+        if let Some(op) = op {
+            // 'd3w' and '3dw' both delete three words, 'dd' deletes a line
+            let (motion_count, motion) = Self::take_count(&rest[1..]);
+            let count = match (count, motion_count) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or(1).saturating_mul(b.unwrap_or(1)).min(MAX_COUNT)),
+            };
+            let n = count.unwrap_or(1);
+            return match motion {
+                [] => Parsed::Pending,
+                [c] if *c == rest[0] => vi(ViCommand::Operate(op, Motion::Lines, n)),
+                _ => Self::motion_event(motion, count, |m| ViCommand::Operate(op, m, n)),
+            };
+        }
+
+        match rest {
+            [] => Parsed::Pending,
+            ['x'] => vi(ViCommand::Operate(Operator::Delete, Motion::Right, n)),
+            ['X'] => vi(ViCommand::Operate(Operator::Delete, Motion::Left, n)),
+            ['D'] => vi(ViCommand::Operate(Operator::Delete, Motion::LineEnd, 1)),
+            ['C'] => vi(ViCommand::Operate(Operator::Change, Motion::LineEnd, 1)),
+            ['s'] => vi(ViCommand::Operate(Operator::Change, Motion::Right, n)),
+            ['S'] => vi(ViCommand::Operate(Operator::Change, Motion::Lines, n)),
+            ['Y'] => vi(ViCommand::Operate(Operator::Yank, Motion::Lines, n)),
+            ['i'] => vi(ViCommand::Insert(InsertAt::Cursor)),
+            ['a'] => vi(ViCommand::Insert(InsertAt::After)),
+            ['I'] => vi(ViCommand::Insert(InsertAt::LineStart)),
+            ['A'] => vi(ViCommand::Insert(InsertAt::LineEnd)),
+            ['o'] => vi(ViCommand::Insert(InsertAt::LineBelow)),
+            ['O'] => vi(ViCommand::Insert(InsertAt::LineAbove)),
+            ['p'] => vi(ViCommand::Paste(false, n)),
+            ['P'] => vi(ViCommand::Paste(true, n)),
+            ['v'] => vi(ViCommand::Visual),
+            [':'] => vi(ViCommand::Command),
+            ['u'] => control(ControlEvent::Undo),
+            ['J'] => control(ControlEvent::JoinLines),
+            ['%'] => control(ControlEvent::MatchBracket),
+            ['/'] => control(ControlEvent::Find),
+            ['.'] => Parsed::Repeat(count),
+            motion => Self::motion_event(motion, count, |m| ViCommand::Move(m, n)),
+        }
+    }
+
+    fn parse_visual(keys: &[char]) -> Parsed {
+        let (count, rest) = Self::take_count(keys);
+        let vi = |command| Parsed::Event(EditorEvent::Vi(command));
+        let control = |event| Parsed::Event(EditorEvent::Control(event));
+
+        match rest {
+            [] => Parsed::Pending,
+            ['d' | 'x'] => vi(ViCommand::VisualOperate(Operator::Delete)),
+            ['c' | 's'] => vi(ViCommand::VisualOperate(Operator::Change)),
+            ['y'] => vi(ViCommand::VisualOperate(Operator::Yank)),
+            ['v'] => control(ControlEvent::Escape),
+            ['>'] => control(ControlEvent::IndentBlock),
+            ['<'] => control(ControlEvent::DedentBlock),
+            [':'] => vi(ViCommand::Command),
+            motion => {
+                let n = count.unwrap_or(1);
+                Self::motion_event(motion, count, |m| ViCommand::Move(m, n))
+            }
+        }
+    }
+}